@@ -1,5 +1,5 @@
-use aoc_2024::AocSolver;
-use clap::{command, Parser};
+use aoc_2024::{params::parse_param, AocSolver, Params};
+use clap::Parser;
 use color_eyre::eyre::Result;
 use paste::paste;
 use std::path::PathBuf;
@@ -10,12 +10,12 @@ use mimalloc::MiMalloc;
 static GLOBAL: MiMalloc = MiMalloc;
 
 macro_rules! pick_solver {
-    ($select:expr, $data:expr, $($day:literal),* $(,)?) => {
+    ($select:expr, $data:expr, $params:expr, $($day:literal),* $(,)?) => {
         paste! {
         match $select {
             $(
                 $day => {
-                    run::<[<Day $day Solver>], _>(&$data)?.pipe(print_solution);
+                    run::<[<Day $day Solver>], _>(&$data, &$params)?.pipe(print_solution);
                 }
             )*
         _ => eprintln!("solution not found"),
@@ -34,6 +34,10 @@ struct Args {
     /// Path to data file, if outside of data dir
     #[arg(short, long)]
     data_file: Option<PathBuf>,
+
+    /// Solver parameter as key=value, such as `rows=103`. May be repeated
+    #[arg(short, long = "param", value_parser = parse_param)]
+    params: Vec<(String, String)>,
 }
 
 fn main() -> Result<()> {
@@ -50,24 +54,27 @@ fn main() -> Result<()> {
         std::fs::read_to_string(path)?
     };
 
-    start(args.day, data)
+    start(args.day, data, args.params.into_iter().collect())
 }
 
 #[allow(clippy::zero_prefixed_literal)]
-fn start(day: u8, data: String) -> Result<()> {
+fn start(day: u8, data: String, params: Params) -> Result<()> {
     use aoc_2024::*;
 
-    pick_solver!(day, data, 01, 02, 03, 04, 05, 06, 07, 08, 09, 10, 11, 12, 13, 14);
+    pick_solver!(day, data, params, 01, 02, 03, 04, 05, 06, 07, 08, 09, 10, 11, 12, 13, 14);
 
     Ok(())
 }
 
-fn run<S, T>(data: &str) -> Result<(T, T)>
+fn run<S, T>(data: &str, params: &Params) -> Result<(T, T)>
 where
     S: AocSolver<Output = T>,
     T: std::fmt::Display,
 {
-    Ok((S::part_1(data), S::part_2(data)))
+    Ok((
+        S::part_1_with_params(data, params)?,
+        S::part_2_with_params(data, params)?,
+    ))
 }
 
 fn print_solution<T>((part1, part2): (T, T))
@@ -1,66 +1,272 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use aoc_2024::{
+    day14::{self, SecurityTeam},
+    Params,
+};
+use clap::Parser;
 use color_eyre::Result;
 use ratatui::{
-    crossterm::event::{self, Event, KeyCode},
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
     layout::{Constraint, Direction, Layout},
     text::Text,
-    widgets::Paragraph,
+    widgets::{Block, Paragraph},
     DefaultTerminal, Frame,
 };
 
-struct App {
-    count: i64,
+/// Time between frames while playing
+const TICK: Duration = Duration::from_millis(100);
+
+/// Number of frames used to decide what counts as low entropy
+const ENTROPY_SAMPLES: i64 = 1000;
+
+/// Interactive viewer for the day 14 bots
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Path to the puzzle input
+    #[arg(default_value = "data/day14.txt")]
+    input: PathBuf,
+
+    /// Number of rows on the map. Inferred from the input when missing
+    #[arg(short, long)]
+    rows: Option<i64>,
+
+    /// Number of columns on the map. Inferred from the input when missing
+    #[arg(short, long)]
+    cols: Option<i64>,
 }
 
-const PROBLEM_INPUT: &str = include_str!("../../data/day14.txt");
+enum Mode {
+    Normal,
+    /// Typing a second to jump to
+    Jump(String),
+}
+
+struct App {
+    bots: SecurityTeam,
+    seconds: i64,
+    playing: bool,
+    /// Seconds advanced per tick while playing. Negative plays backwards.
+    speed: i64,
+    /// Positions repeat after this many seconds
+    period: i64,
+    /// Frames with an entropy below this are worth a look
+    low_entropy: f64,
+    mode: Mode,
+    status: String,
+}
 
 impl App {
-    fn new() -> Self {
-        Self { count: 0 }
+    fn new(bots: SecurityTeam) -> Self {
+        let period = bots.period();
+
+        // most frames are noise, so anything well below the typical entropy stands out
+        let entropies = {
+            let mut bots = bots.clone();
+            (0..period.min(ENTROPY_SAMPLES))
+                .map(|_| {
+                    let entropy = bots.entropy();
+                    bots.timeshift(1);
+                    entropy
+                })
+                .collect::<Vec<_>>()
+        };
+        let n = entropies.len() as f64;
+        let mean = entropies.iter().sum::<f64>() / n;
+        let std_dev = (entropies.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / n).sqrt();
+
+        Self {
+            bots,
+            seconds: 0,
+            playing: false,
+            speed: 1,
+            period,
+            low_entropy: mean - 3.0 * std_dev,
+            mode: Mode::Normal,
+            status: String::default(),
+        }
     }
 
-    fn draw(&self, frame: &mut Frame) {
-        use aoc_2024::day14::SecurityTeam;
+    /// Moves by `seconds`, unless the second moved to doesn't fit in an `i64`.
+    fn step(&mut self, seconds: i64) {
+        match self.seconds.checked_add(seconds) {
+            Some(target) => {
+                self.bots.timeshift(seconds);
+                self.seconds = target;
+            }
+            None => {
+                self.playing = false;
+                self.status = "invalid second: out of range".to_string();
+            }
+        }
+    }
 
-        let dimensions = (103, 101);
+    fn jump_to(&mut self, seconds: i64) {
+        match seconds.checked_sub(self.seconds) {
+            Some(offset) => self.step(offset),
+            None => self.status = format!("invalid second: {seconds}"),
+        }
+    }
 
-        let mut bots = SecurityTeam::new(PROBLEM_INPUT, dimensions);
-        bots.timeshift(self.count);
+    /// Moves forward to the next frame with low entropy, searching at most one period.
+    fn seek_low_entropy(&mut self) {
+        let mut bots = self.bots.clone();
+        for offset in 1..=self.period {
+            bots.timeshift(1);
+            if bots.entropy() < self.low_entropy {
+                self.status = match self.seconds.checked_add(offset) {
+                    Some(seconds) => {
+                        self.bots = bots;
+                        self.seconds = seconds;
+                        format!("found low entropy at {seconds}")
+                    }
+                    None => "invalid second: out of range".to_string(),
+                };
+                return;
+            }
+        }
+        self.status = "no low entropy frames".to_string();
+    }
 
+    fn draw(&self, frame: &mut Frame) {
         let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(vec![Constraint::Percentage(1), Constraint::Percentage(99)])
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Min(0), Constraint::Length(32)])
             .split(frame.area());
 
-        let minutes = Paragraph::new(format!("Minutes: {}", self.count));
-        frame.render_widget(minutes, layout[0]);
+        let map = Text::raw(self.bots.to_string());
+        frame.render_widget(
+            Paragraph::new(map).block(Block::bordered().title(format!(" {}s ", self.seconds))),
+            layout[0],
+        );
 
-        let map = Text::raw(bots.to_string());
-        frame.render_widget(map, layout[1]);
+        let quadrants = self
+            .bots
+            .quadrants()
+            .map(|quadrant| self.bots.in_quadrant(quadrant));
+        let mut lines = vec![
+            format!("Seconds: {}", self.seconds),
+            format!(
+                "Playback: {} at {}/tick",
+                if self.playing { "playing" } else { "paused" },
+                self.speed
+            ),
+            String::default(),
+            format!("Top left: {}", quadrants[0]),
+            format!("Top right: {}", quadrants[1]),
+            format!("Bottom left: {}", quadrants[2]),
+            format!("Bottom right: {}", quadrants[3]),
+            format!("Safety factor: {}", self.bots.safety_factor()),
+            format!("Entropy: {:.3}", self.bots.entropy()),
+            format!("Largest cluster: {}", self.bots.largest_cluster()),
+            format!("Low entropy: < {:.3}", self.low_entropy),
+            String::default(),
+            "space  play/pause".to_string(),
+            "+ -    speed".to_string(),
+            "r      reverse".to_string(),
+            "← →    step 1".to_string(),
+            "pg ↑↓  step 100".to_string(),
+            "g      jump to second".to_string(),
+            "n      next low entropy".to_string(),
+            "q      quit".to_string(),
+            String::default(),
+        ];
+        match &self.mode {
+            Mode::Jump(digits) => lines.push(format!("Jump to: {digits}_")),
+            Mode::Normal => lines.push(self.status.clone()),
+        }
+
+        frame.render_widget(
+            Paragraph::new(lines.join("\n")).block(Block::bordered().title(" Bots ")),
+            layout[1],
+        );
+    }
+
+    /// Returns `false` when the viewer should quit.
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        match &mut self.mode {
+            Mode::Jump(digits) => match key {
+                KeyCode::Char(ch @ ('0'..='9' | '-')) => digits.push(ch),
+                KeyCode::Backspace => {
+                    digits.pop();
+                }
+                KeyCode::Enter => {
+                    let digits = std::mem::take(digits);
+                    self.mode = Mode::Normal;
+                    match digits.parse() {
+                        Ok(seconds) => self.jump_to(seconds),
+                        Err(_) => self.status = format!("invalid second: {digits}"),
+                    }
+                }
+                KeyCode::Esc => self.mode = Mode::Normal,
+                _ => (),
+            },
+            Mode::Normal => match key {
+                KeyCode::Char('q') | KeyCode::Esc => return false,
+                KeyCode::Char(' ') => self.playing = !self.playing,
+                KeyCode::Char('+' | '=') => {
+                    self.speed = (self.speed * 2).clamp(-self.period, self.period)
+                }
+                KeyCode::Char('-') => {
+                    self.speed = (self.speed.abs() / 2).max(1) * self.speed.signum()
+                }
+                KeyCode::Char('r') => self.speed = -self.speed,
+                KeyCode::Char('g') => self.mode = Mode::Jump(String::default()),
+                KeyCode::Char('n') => {
+                    self.playing = false;
+                    self.seek_low_entropy();
+                }
+                KeyCode::Left => self.step(-1),
+                KeyCode::Right => self.step(1),
+                KeyCode::PageUp => self.step(-100),
+                KeyCode::PageDown => self.step(100),
+                _ => (),
+            },
+        }
+        true
     }
 
     fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+        let mut last_tick = Instant::now();
         loop {
             terminal.draw(|frame| self.draw(frame))?;
 
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Left => self.count -= 1,
-                    KeyCode::Right => self.count += 1,
-                    KeyCode::PageUp => self.count -= 100,
-                    KeyCode::PageDown => self.count += 100,
-                    _ => {
+            // block on input while paused
+            let has_event = !self.playing || event::poll(TICK.saturating_sub(last_tick.elapsed()))?;
+            if has_event {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press && !self.handle_key(key.code) {
                         return Ok(());
                     }
                 }
             }
+
+            if self.playing && last_tick.elapsed() >= TICK {
+                self.step(self.speed);
+                last_tick = Instant::now();
+            }
         }
     }
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?; // augment errors / panics with easy to read messages
+
+    let args = Args::parse();
+    let input = std::fs::read_to_string(&args.input)?;
+
+    let params = [("rows", args.rows), ("cols", args.cols)]
+        .into_iter()
+        .filter_map(|(key, size)| Some((key.to_string(), size?.to_string())))
+        .collect::<Params>();
+    let bots = SecurityTeam::new(&input, day14::dimensions(&input, &params)?);
+
     let terminal = ratatui::init();
-    let app_result = App::new().run(terminal);
+    let app_result = App::new(bots).run(terminal);
     ratatui::restore();
     app_result
 }
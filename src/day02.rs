@@ -40,7 +40,7 @@ impl AocSolver for Day02Solver {
 mod parser {
     use super::{Levels, RawLevels, Reports};
 
-    pub fn split_into_levels(raw: &str) -> RawLevels {
+    pub fn split_into_levels(raw: &str) -> RawLevels<'_> {
         raw.split(' ').collect()
     }
 
@@ -1,8 +1,6 @@
-use std::{
-    ops::Index,
-    sync::atomic::{AtomicU32, Ordering},
-};
+use std::ops::Index;
 
+use fxhash::FxHashSet;
 use rayon::prelude::*;
 
 use crate::AocSolver;
@@ -19,49 +17,240 @@ impl AocSolver for Day06Solver {
         map.visited_count()
     }
 
+    // Strategy:
+    // Only spaces on the original path can change where the guard goes. For each of them, start
+    // from the state just before the guard first steps onto it, then jump from turn to turn using
+    // a table of where the guard stops in each direction.
     fn part_2(input: &str) -> Self::Output {
-        let mut map = Map::from(input);
-
+        let map = Map::from(input);
         let guard = map.guard_position().unwrap();
+        map.find_looping_obstructions(guard).len() as u32
+    }
+}
 
-        // simulate initial guard movement so we know where we can add obstacles
-        map.simulate_guard(guard);
-
-        let visited_spaces = map.iter_visited_spaces().collect::<Vec<_>>();
+/// An obstruction that traps the guard in a loop, and the patrol that gets trapped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopingObstruction {
+    pub pos: Pos,
+    pub patrol: Patrol,
+}
 
-        let possible_loops = AtomicU32::new(0);
+/// Where the guard stops when walking in each direction from each space.
+#[derive(Debug, Clone)]
+struct JumpTable {
+    cols: usize,
+    // the space before the next obstacle, or `None` when the guard walks off the map
+    stops: Vec<[Option<Pos>; 4]>,
+}
 
-        visited_spaces.par_iter().for_each(|pos| {
-            let mut map = map.clone();
-            map.add_obstruction(pos);
-            match map.simulate_guard(guard) {
-                SimulationResult::StuckInLoop => {
-                    possible_loops.fetch_add(1, Ordering::Relaxed);
+impl JumpTable {
+    fn new(map: &Map) -> Self {
+        let (rows, cols) = map.bounds();
+        let mut stops = vec![[None; 4]; rows * cols];
+
+        for col in 0..cols {
+            let mut stop = None;
+            for row in 0..rows {
+                stops[row * cols + col][Direction::Up.index()] = stop;
+                if map.has_obstacle((row, col).into()) {
+                    stop = Some((row + 1, col).into());
                 }
-                SimulationResult::ExitMap => (),
             }
-        });
-        possible_loops.load(Ordering::Relaxed)
+            let mut stop = None;
+            for row in (0..rows).rev() {
+                stops[row * cols + col][Direction::Down.index()] = stop;
+                if map.has_obstacle((row, col).into()) {
+                    stop = row.checked_sub(1).map(|row| (row, col).into());
+                }
+            }
+        }
+        for row in 0..rows {
+            let mut stop = None;
+            for col in 0..cols {
+                stops[row * cols + col][Direction::Left.index()] = stop;
+                if map.has_obstacle((row, col).into()) {
+                    stop = Some((row, col + 1).into());
+                }
+            }
+            let mut stop = None;
+            for col in (0..cols).rev() {
+                stops[row * cols + col][Direction::Right.index()] = stop;
+                if map.has_obstacle((row, col).into()) {
+                    stop = col.checked_sub(1).map(|col| (row, col).into());
+                }
+            }
+        }
+
+        Self { cols, stops }
+    }
+
+    /// Returns where the guard stops, with `obstruction` added to the map.
+    #[inline(always)]
+    fn stop(&self, guard: Guard, obstruction: Pos) -> Option<Pos> {
+        let stop = self.stops[guard.pos.row() * self.cols + guard.pos.col()][guard.facing.index()];
+        let (from, to) = (guard.pos, obstruction);
+
+        // distance to the obstruction, if it's ahead of the guard
+        let ahead = match guard.facing {
+            Direction::Up => (to.col == from.col && to.row < from.row).then(|| from.row - to.row),
+            Direction::Down => (to.col == from.col && to.row > from.row).then(|| to.row - from.row),
+            Direction::Left => (to.row == from.row && to.col < from.col).then(|| from.col - to.col),
+            Direction::Right => {
+                (to.row == from.row && to.col > from.col).then(|| to.col - from.col)
+            }
+        };
+        let Some(ahead) = ahead else {
+            return stop;
+        };
+
+        let blocked = match stop {
+            Some(stop) => ahead <= from.row.abs_diff(stop.row) + from.col.abs_diff(stop.col),
+            None => true,
+        };
+        if blocked {
+            Some(match guard.facing {
+                Direction::Up => (to.row + 1, to.col).into(),
+                Direction::Down => (to.row - 1, to.col).into(),
+                Direction::Left => (to.row, to.col + 1).into(),
+                Direction::Right => (to.row, to.col - 1).into(),
+            })
+        } else {
+            stop
+        }
+    }
+
+    /// Returns `true` when the guard gets stuck in a loop once `obstruction` is added.
+    fn loops(&self, mut guard: Guard, obstruction: Pos) -> bool {
+        // a loop repeats a turn, so only the states after each turn need checking
+        let mut turns = FxHashSet::default();
+        while let Some(pos) = self.stop(guard, obstruction) {
+            guard = Guard {
+                pos,
+                facing: guard.facing.turn_right(),
+            };
+            if !turns.insert(guard) {
+                return true;
+            }
+        }
+        false
     }
 }
 
-enum SimulationResult {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulationResult {
     ExitMap,
-    StuckInLoop,
+    /// The guard returned to `entry`, facing the same way, after `length` moves.
+    StuckInLoop { entry: Guard, length: usize },
+}
+
+/// Every state of the guard, from the start until leaving the map or repeating a state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patrol {
+    path: Vec<Guard>,
+    result: SimulationResult,
+}
+
+impl Patrol {
+    pub fn path(&self) -> &[Guard] {
+        &self.path
+    }
+
+    pub fn result(&self) -> SimulationResult {
+        self.result
+    }
+
+    /// Returns the states that repeat forever, or an empty slice if the guard leaves the map.
+    pub fn loop_path(&self) -> &[Guard] {
+        match self.result {
+            SimulationResult::StuckInLoop { length, .. } => {
+                &self.path[self.path.len() - length..]
+            }
+            SimulationResult::ExitMap => &[],
+        }
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
-struct Map {
+pub struct Map {
     inner: Vec<Vec<char>>,
 }
 
 impl Map {
+    /// Returns every space where an obstruction would trap the guard in a loop, in the order the
+    /// guard first reaches them.
+    pub fn find_looping_obstructions(&self, guard: Guard) -> Vec<Pos> {
+        let mut map = self.clone();
+        let patrol = map.trace_guard(guard);
+        let jumps = JumpTable::new(&map);
+
+        // Only spaces on the original path can change where the guard goes. The guard walks the
+        // same way until they first reach one, so start from the state just before that.
+        let mut seen = FxHashSet::default();
+        seen.insert(guard.pos);
+        let candidates = patrol
+            .path
+            .windows(2)
+            .filter(|states| seen.insert(states[1].pos))
+            .map(|states| (states[1].pos, states[0]))
+            .collect::<Vec<_>>();
+
+        candidates
+            .par_iter()
+            .filter(|(obstruction, before)| jumps.loops(*before, *obstruction))
+            .map(|(obstruction, _)| *obstruction)
+            .collect()
+    }
+
+    /// Returns every obstruction that traps the guard in a loop, along with the guard's patrol.
+    pub fn obstruction_report(&self, guard: Guard) -> Vec<LoopingObstruction> {
+        self.find_looping_obstructions(guard)
+            .into_iter()
+            .map(|pos| {
+                let mut map = self.clone();
+                map.add_obstruction(&pos);
+                LoopingObstruction {
+                    pos,
+                    patrol: map.trace_guard(guard),
+                }
+            })
+            .collect()
+    }
+
+    /// Draws the patrol over the map: `|` and `-` where the guard walked up or down and left or
+    /// right, `+` where they did both or turned, and `O` at the obstruction. The starting space
+    /// is left as it is.
+    pub fn render_patrol(&self, patrol: &Patrol, obstruction: Option<Pos>) -> String {
+        let mut rendered = self.inner.clone();
+        let start = patrol.path.first().map(|guard| guard.pos);
+        for guard in &patrol.path {
+            if Some(guard.pos) == start {
+                continue;
+            }
+            let ch = &mut rendered[guard.pos.row()][guard.pos.col()];
+            let vertical = matches!(guard.facing, Direction::Up | Direction::Down);
+            *ch = match (*ch, vertical) {
+                ('|', false) | ('-', true) | ('+', _) => '+',
+                (_, true) => '|',
+                (_, false) => '-',
+            };
+        }
+        if let Some(pos) = obstruction {
+            rendered[pos.row()][pos.col()] = 'O';
+        }
+
+        rendered
+            .iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     #[inline(always)]
     fn has_obstacle(&self, pos: Pos) -> bool {
         is_obstacle(self.inner[pos.row()][pos.col()])
     }
 
-    fn guard_position(&self) -> Option<Guard> {
+    pub fn guard_position(&self) -> Option<Guard> {
         self.inner.iter().enumerate().find_map(|(i, col)| {
             col.iter()
                 .enumerate()
@@ -89,23 +278,48 @@ impl Map {
         self.inner[pos.row()][pos.col()] = 'X';
     }
 
-    fn simulate_guard(&mut self, mut guard: Guard) -> SimulationResult {
-        let mut loop_counter = 0;
+    fn simulate_guard(&mut self, guard: Guard) -> SimulationResult {
+        self.trace_guard(guard).result
+    }
+
+    /// Moves the guard until they leave the map or get stuck in a loop, marking each visited
+    /// space.
+    ///
+    /// A loop is found when the guard is on a space facing a direction they already faced there.
+    fn trace_guard(&mut self, mut guard: Guard) -> Patrol {
+        let cols = self.bounds().1;
+        // directions the guard has faced on each space
+        let mut seen = vec![0_u8; self.bounds().0 * cols];
+        let mut path = Vec::default();
         loop {
+            let faced = &mut seen[guard.pos.row() * cols + guard.pos.col()];
+            if *faced & guard.facing.bit() != 0 {
+                let start = path.iter().position(|state| *state == guard).unwrap();
+                let length = path.len() - start;
+                return Patrol {
+                    path,
+                    result: SimulationResult::StuckInLoop {
+                        entry: guard,
+                        length,
+                    },
+                };
+            }
+            *faced |= guard.facing.bit();
+            path.push(guard);
+
             match guard.next(self) {
                 GuardMovement::Straight { old, .. } => {
                     self.set_visited(old);
                 }
-                GuardMovement::Turned { .. } => {}
+                GuardMovement::Turned => {}
                 GuardMovement::OffMap { old } => {
                     self.set_visited(old);
-                    break SimulationResult::ExitMap;
+                    return Patrol {
+                        path,
+                        result: SimulationResult::ExitMap,
+                    };
                 }
             }
-            loop_counter += 1;
-            if loop_counter > 6500 {
-                break SimulationResult::StuckInLoop;
-            }
         }
     }
 
@@ -114,15 +328,6 @@ impl Map {
         self.inner[pos.row()][pos.col()] = '#';
     }
 
-    fn iter_visited_spaces(&self) -> impl Iterator<Item = Pos> + '_ {
-        self.inner.iter().enumerate().flat_map(|(i, row)| {
-            row.iter()
-                .enumerate()
-                .filter_map(|(j, &ch)| if ch == 'X' { Some((i, j).into()) } else { None })
-                .collect::<Vec<_>>()
-        })
-    }
-
     fn visited_count(&self) -> u32 {
         self.inner
             .iter()
@@ -153,14 +358,42 @@ impl From<&str> for Map {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Direction {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
     Up,
     Down,
     Left,
     Right,
 }
 
+impl Direction {
+    #[inline(always)]
+    fn index(self) -> usize {
+        match self {
+            Direction::Up => 0,
+            Direction::Down => 1,
+            Direction::Left => 2,
+            Direction::Right => 3,
+        }
+    }
+
+    /// Returns a distinct bit for each direction
+    #[inline(always)]
+    fn bit(self) -> u8 {
+        1 << self.index()
+    }
+
+    #[inline(always)]
+    fn turn_right(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+}
+
 impl From<char> for Direction {
     fn from(ch: char) -> Self {
         match ch {
@@ -173,17 +406,17 @@ impl From<char> for Direction {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Pos {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pos {
     row: usize,
     col: usize,
 }
 
 impl Pos {
-    fn row(&self) -> usize {
+    pub fn row(&self) -> usize {
         self.row
     }
-    fn col(&self) -> usize {
+    pub fn col(&self) -> usize {
         self.col
     }
 }
@@ -196,8 +429,8 @@ impl From<(usize, usize)> for Pos {
 }
 
 // Row/Col coordinate for the grid
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Guard {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Guard {
     pos: Pos,
     facing: Direction,
 }
@@ -209,6 +442,14 @@ enum GuardMovement {
 }
 
 impl Guard {
+    pub fn pos(&self) -> Pos {
+        self.pos
+    }
+
+    pub fn facing(&self) -> Direction {
+        self.facing
+    }
+
     #[inline(always)]
     fn next(&mut self, map: &Map) -> GuardMovement {
         match self.facing {
@@ -354,6 +595,124 @@ mod tests {
         assert_eq!(map, Map::from(SAMPLE_PATH));
     }
 
+    #[test]
+    fn finds_loop_entry_and_length() {
+        let mut map = Map::from(
+            r#".#...
+....#
+#^...
+...#."#,
+        );
+        let guard = map.guard_position().unwrap();
+        let patrol = map.trace_guard(guard);
+
+        assert_eq!(
+            patrol.result,
+            SimulationResult::StuckInLoop {
+                entry: guard,
+                length: 10
+            }
+        );
+        assert_eq!(patrol.path.len(), 10);
+        assert_eq!(
+            patrol.path[5],
+            Guard {
+                pos: Pos { row: 1, col: 3 },
+                facing: Direction::Down
+            }
+        );
+    }
+
+    #[test]
+    fn finds_loop_entered_after_start() {
+        let mut map = Map::from(SAMPLE);
+        map.add_obstruction(&Pos { row: 7, col: 6 });
+        let guard = map.guard_position().unwrap();
+        let patrol = map.trace_guard(guard);
+
+        let SimulationResult::StuckInLoop { entry, length } = patrol.result else {
+            panic!("expected a loop");
+        };
+        let start = patrol.path.iter().position(|state| *state == entry).unwrap();
+        assert!(start > 0);
+        assert_eq!(patrol.path.len() - start, length);
+    }
+
+    #[test]
+    fn follows_long_paths_without_loops() {
+        let input = ".\n".repeat(10_000) + "^";
+        let mut map = Map::from(input.as_str());
+        let guard = map.guard_position().unwrap();
+        let patrol = map.trace_guard(guard);
+
+        assert_eq!(patrol.result, SimulationResult::ExitMap);
+        assert_eq!(patrol.path.len(), 10_001);
+        assert_eq!(map.visited_count(), 10_001);
+    }
+
+    #[test]
+    fn jump_table_agrees_with_step_simulation() {
+        let map = Map::from(SAMPLE);
+        let guard = map.guard_position().unwrap();
+        let jumps = JumpTable::new(&map);
+        let (rows, cols) = map.bounds();
+
+        let mut loops = 0;
+        for pos in (0..rows).flat_map(|row| (0..cols).map(move |col| Pos::from((row, col)))) {
+            if pos == guard.pos || map.has_obstacle(pos) {
+                continue;
+            }
+            let mut obstructed = map.clone();
+            obstructed.add_obstruction(&pos);
+            let expected = matches!(
+                obstructed.simulate_guard(guard),
+                SimulationResult::StuckInLoop { .. }
+            );
+            assert_eq!(jumps.loops(guard, pos), expected, "obstruction at {pos:?}");
+            loops += expected as u32;
+        }
+        assert_eq!(loops, 6);
+    }
+
+    #[test]
+    fn reports_looping_obstructions() {
+        let map = Map::from(SAMPLE);
+        let guard = map.guard_position().unwrap();
+        let report = map.obstruction_report(guard);
+
+        let mut positions = report
+            .iter()
+            .map(|obstruction| (obstruction.pos.row(), obstruction.pos.col()))
+            .collect::<Vec<_>>();
+        positions.sort();
+        assert_eq!(positions, [(6, 3), (7, 6), (7, 7), (8, 1), (8, 3), (9, 7)]);
+
+        for obstruction in &report {
+            let patrol = &obstruction.patrol;
+            let SimulationResult::StuckInLoop { entry, length } = patrol.result() else {
+                panic!("expected a loop at {:?}", obstruction.pos);
+            };
+            assert_eq!(patrol.loop_path().len(), length);
+            assert_eq!(patrol.loop_path()[0], entry);
+        }
+    }
+
+    #[test]
+    fn renders_obstructed_patrol() {
+        let map = Map::from(SAMPLE);
+        let guard = map.guard_position().unwrap();
+        let report = map.obstruction_report(guard);
+
+        let obstruction = report
+            .iter()
+            .find(|obstruction| obstruction.pos == Pos { row: 8, col: 3 })
+            .unwrap();
+        assert_eq!(
+            map.render_patrol(&obstruction.patrol, Some(obstruction.pos)),
+            SAMPLE_OBSTRUCTION
+        );
+    }
+
     #[test]
     fn solves_part_1() {
         let answer = Day06Solver::part_1(SAMPLE);
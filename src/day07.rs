@@ -1,11 +1,17 @@
 use rayon::prelude::*;
 use smallvec::SmallVec;
-use std::ops::Index;
+use std::ops::{ControlFlow, Index};
 
 use crate::AocSolver;
 
 pub struct Day07Solver;
 
+/// Operators available in part 1
+pub const PART_1_OPERATORS: [&dyn Operator; 2] = [&Add, &Mul];
+
+/// Operators available in part 2
+pub const PART_2_OPERATORS: [&dyn Operator; 3] = [&Add, &Mul, &Concat];
+
 impl AocSolver for Day07Solver {
     type Output = u64;
 
@@ -15,7 +21,7 @@ impl AocSolver for Day07Solver {
             .parts
             .par_iter()
             .filter_map(|part| {
-                if part.can_be_made_true(&[add, mul]) {
+                if part.can_be_made_true(&PART_1_OPERATORS) {
                     Some(part.answer)
                 } else {
                     None
@@ -30,14 +36,14 @@ impl AocSolver for Day07Solver {
         let (part_1_true, check_part_2): (Vec<_>, Vec<_>) = equation_parts
             .parts
             .par_iter()
-            .partition(|part| part.can_be_made_true(&[add, mul]));
+            .partition(|part| part.can_be_made_true(&PART_1_OPERATORS));
 
         let part_1_sum = part_1_true.into_iter().map(|part| part.answer).sum::<u64>();
 
         let part_2_sum = check_part_2
             .par_iter()
             .filter_map(|part| {
-                if part.can_be_made_true(&[add, mul, concat]) {
+                if part.can_be_made_true(&PART_2_OPERATORS) {
                     Some(part.answer)
                 } else {
                     None
@@ -49,49 +55,218 @@ impl AocSolver for Day07Solver {
     }
 }
 
+/// An operator that can be placed between two operands. Operators are always evaluated left to
+/// right.
+pub trait Operator: Sync {
+    fn name(&self) -> &'static str;
+
+    /// How the operator is written in an expression
+    fn symbol(&self) -> &'static str;
+
+    /// Returns `None` if the result doesn't fit in a `u64`.
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64>;
+}
+
+impl std::fmt::Debug for dyn Operator + '_ {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Add;
+
+impl Operator for Add {
+    fn name(&self) -> &'static str {
+        "add"
+    }
+
+    fn symbol(&self) -> &'static str {
+        "+"
+    }
+
+    #[inline(always)]
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+        lhs.checked_add(rhs)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Mul;
+
+impl Operator for Mul {
+    fn name(&self) -> &'static str {
+        "multiply"
+    }
+
+    fn symbol(&self) -> &'static str {
+        "*"
+    }
+
+    #[inline(always)]
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+        lhs.checked_mul(rhs)
+    }
+}
+
+/// Joins the digits of both operands: `12 || 345` is `12345`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Concat;
+
+impl Operator for Concat {
+    fn name(&self) -> &'static str {
+        "concatenate"
+    }
+
+    fn symbol(&self) -> &'static str {
+        "||"
+    }
+
+    #[inline(always)]
+    fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+        concat(lhs, rhs)
+    }
+}
+
 #[derive(Clone, Debug)]
-struct CalibrationEquation {
+pub struct CalibrationEquation {
     answer: u64,
     operands: OperandVec,
 }
 
-type MathOp = fn(u64, u64) -> u64;
 type OperandVec = SmallVec<[u64; 12]>;
+type OperatorVec<'a> = SmallVec<[&'a dyn Operator; 11]>;
 
 impl CalibrationEquation {
-    fn can_be_made_true(&self, operations: &[MathOp]) -> bool {
-        self.can_be_made_true_impl(operations, &self.operands, 0, self.operands[0])
+    pub fn answer(&self) -> u64 {
+        self.answer
+    }
+
+    pub fn operands(&self) -> &[u64] {
+        &self.operands
     }
 
-    fn can_be_made_true_impl(
+    pub fn can_be_made_true(&self, operators: &[&dyn Operator]) -> bool {
+        self.solve_one(operators).is_some()
+    }
+
+    /// Returns the first assignment of operators that makes the equation true, trying
+    /// `operators` in order at each position.
+    pub fn solve_one<'a>(&'a self, operators: &[&'a dyn Operator]) -> Option<Solution<'a>> {
+        let mut found = None;
+        self.search(operators, |chosen| {
+            found = Some(Solution {
+                equation: self,
+                operators: chosen.iter().copied().collect(),
+            });
+            ControlFlow::Break(())
+        });
+        found
+    }
+
+    /// Returns every assignment of operators that makes the equation true.
+    pub fn solve_all<'a>(&'a self, operators: &[&'a dyn Operator]) -> Vec<Solution<'a>> {
+        let mut found = Vec::default();
+        self.search(operators, |chosen| {
+            found.push(Solution {
+                equation: self,
+                operators: chosen.iter().copied().collect(),
+            });
+            ControlFlow::Continue(())
+        });
+        found
+    }
+
+    /// Returns the number of assignments of operators that make the equation true.
+    pub fn count_solutions(&self, operators: &[&dyn Operator]) -> usize {
+        let mut count = 0;
+        self.search(operators, |_| {
+            count += 1;
+            ControlFlow::Continue(())
+        });
+        count
+    }
+
+    /// Calls `found` with each assignment of operators that makes the equation true, until it
+    /// returns `ControlFlow::Break`.
+    fn search<'a, F>(&self, operators: &[&'a dyn Operator], mut found: F)
+    where
+        F: FnMut(&[&'a dyn Operator]) -> ControlFlow<()>,
+    {
+        let mut chosen = OperatorVec::default();
+        let _ = self.search_impl(operators, 0, self.operands[0], &mut chosen, &mut found);
+    }
+
+    fn search_impl<'a, F>(
         &self,
-        operations: &[MathOp],
-        operands: &[u64],
+        operators: &[&'a dyn Operator],
         operand_index: usize,
         current_result: u64,
-    ) -> bool {
-        // return the result once we've reached the end
-        if operand_index == operands.len() - 1 {
-            return current_result == self.answer;
+        chosen: &mut OperatorVec<'a>,
+        found: &mut F,
+    ) -> ControlFlow<()>
+    where
+        F: FnMut(&[&'a dyn Operator]) -> ControlFlow<()>,
+    {
+        // check the result once we've reached the end
+        if operand_index == self.operands.len() - 1 {
+            if current_result == self.answer {
+                return found(chosen);
+            }
+            return ControlFlow::Continue(());
         }
 
-        for &op in operations {
-            let next_result = op(current_result, operands[operand_index + 1]);
+        for &op in operators {
+            let Some(next_result) = op.apply(current_result, self.operands[operand_index + 1])
+            else {
+                continue;
+            };
 
-            if self.can_be_made_true_impl(operations, operands, operand_index + 1, next_result) {
-                return true;
-            }
+            chosen.push(op);
+            let flow = self.search_impl(operators, operand_index + 1, next_result, chosen, found);
+            chosen.pop();
+            flow?;
         }
 
-        false
+        ControlFlow::Continue(())
+    }
+}
+
+/// Operators that make a `CalibrationEquation` true. Displayed as the full expression, such as
+/// `81 + 40 * 27 = 3267`.
+#[derive(Clone, Debug)]
+pub struct Solution<'a> {
+    equation: &'a CalibrationEquation,
+    operators: OperatorVec<'a>,
+}
+
+impl Solution<'_> {
+    pub fn operators(&self) -> impl Iterator<Item = &dyn Operator> + '_ {
+        self.operators.iter().copied()
+    }
+}
+
+impl std::fmt::Display for Solution<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.equation.operands[0])?;
+        for (op, operand) in self.operators.iter().zip(&self.equation.operands[1..]) {
+            write!(f, " {} {}", op.symbol(), operand)?;
+        }
+        write!(f, " = {}", self.equation.answer)
     }
 }
 
 #[derive(Clone, Debug)]
-struct EquationParts {
+pub struct EquationParts {
     parts: Vec<CalibrationEquation>,
 }
 
+impl EquationParts {
+    pub fn iter(&self) -> impl Iterator<Item = &CalibrationEquation> {
+        self.parts.iter()
+    }
+}
+
 impl Index<usize> for EquationParts {
     type Output = CalibrationEquation;
 
@@ -122,24 +297,10 @@ impl From<&str> for EquationParts {
 }
 
 #[inline(always)]
-fn add(lhs: u64, rhs: u64) -> u64 {
-    lhs + rhs
-}
+fn concat(a: u64, b: u64) -> Option<u64> {
+    let b_digits = if b == 0 { 1 } else { b.ilog10() + 1 };
 
-#[inline(always)]
-fn mul(lhs: u64, rhs: u64) -> u64 {
-    lhs * rhs
-}
-
-#[inline(always)]
-fn concat(a: u64, b: u64) -> u64 {
-    let b_digits = if b == 0 {
-        1
-    } else {
-        (b as f64).log10().floor() as u32 + 1
-    };
-
-    a * 10u64.pow(b_digits) + b
+    a.checked_mul(10u64.checked_pow(b_digits)?)?.checked_add(b)
 }
 
 #[cfg(test)]
@@ -169,7 +330,7 @@ mod tests {
     fn left_to_right_evaluation() {
         let equation_parts = EquationParts::from(SAMPLE);
 
-        let answer = equation_parts[1].can_be_made_true(&[add, mul]);
+        let answer = equation_parts[1].can_be_made_true(&[&Add, &Mul]);
         assert!(answer);
     }
 
@@ -182,17 +343,88 @@ mod tests {
 
     #[test]
     fn concat_two_numbers() {
-        assert_eq!(concat(8, 4), 84);
-        assert_eq!(concat(8, 40), 840);
-        assert_eq!(concat(8, 400), 8400);
+        assert_eq!(concat(8, 4), Some(84));
+        assert_eq!(concat(8, 40), Some(840));
+        assert_eq!(concat(8, 400), Some(8400));
+
+        assert_eq!(concat(80, 4), Some(804));
+        assert_eq!(concat(80, 40), Some(8040));
+        assert_eq!(concat(80, 400), Some(80400));
+
+        assert_eq!(concat(800, 4), Some(8004));
+        assert_eq!(concat(800, 40), Some(80040));
+        assert_eq!(concat(800, 400), Some(800400));
+    }
 
-        assert_eq!(concat(80, 4), 804);
-        assert_eq!(concat(80, 40), 8040);
-        assert_eq!(concat(80, 400), 80400);
+    #[test]
+    fn concat_overflow() {
+        assert_eq!(concat(u64::MAX / 10, 9), None);
+        assert_eq!(concat(1, 0), Some(10));
+    }
+
+    #[test]
+    fn solves_with_operators() {
+        let equation_parts = EquationParts::from(SAMPLE);
+
+        let solution = equation_parts[1].solve_one(&PART_1_OPERATORS).unwrap();
+        assert_eq!(solution.to_string(), "81 + 40 * 27 = 3267");
+        assert_eq!(
+            solution.operators().map(|op| op.name()).collect::<Vec<_>>(),
+            ["add", "multiply"]
+        );
+
+        let all = equation_parts[1]
+            .solve_all(&PART_1_OPERATORS)
+            .iter()
+            .map(|solution| solution.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(all, ["81 + 40 * 27 = 3267", "81 * 40 + 27 = 3267"]);
+        assert_eq!(equation_parts[1].count_solutions(&PART_1_OPERATORS), 2);
+
+        assert!(equation_parts[2].solve_one(&PART_2_OPERATORS).is_none());
+        assert_eq!(
+            equation_parts[4]
+                .solve_one(&PART_2_OPERATORS)
+                .unwrap()
+                .to_string(),
+            "6 * 8 || 6 * 15 = 7290"
+        );
+    }
+
+    #[test]
+    fn counts_solutions() {
+        let equation_parts = EquationParts::from(SAMPLE);
+
+        let counts = equation_parts
+            .iter()
+            .map(|equation| equation.count_solutions(&PART_2_OPERATORS))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, [1, 2, 0, 1, 1, 0, 1, 0, 1]);
+    }
+
+    /// An operator defined outside the solver
+    struct Sub;
+
+    impl Operator for Sub {
+        fn name(&self) -> &'static str {
+            "subtract"
+        }
+
+        fn symbol(&self) -> &'static str {
+            "-"
+        }
+
+        fn apply(&self, lhs: u64, rhs: u64) -> Option<u64> {
+            lhs.checked_sub(rhs)
+        }
+    }
+
+    #[test]
+    fn accepts_new_operators() {
+        let equation_parts = EquationParts::from("3: 10 4 3");
 
-        assert_eq!(concat(800, 4), 8004);
-        assert_eq!(concat(800, 40), 80040);
-        assert_eq!(concat(800, 400), 800400);
+        let solution = equation_parts[0].solve_one(&[&Add, &Sub]).unwrap();
+        assert_eq!(solution.to_string(), "10 - 4 - 3 = 3");
     }
 
     #[test]
@@ -1,12 +1,13 @@
 use crate::AocSolver;
 use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap},
     ops::Index,
-    sync::{
-        atomic::{AtomicUsize, Ordering},
-        Arc,
-    },
 };
 
+/// Largest file size, as each size in the disk map is a single digit
+const MAX_FILE_SIZE: usize = 9;
+
 pub struct Day09Solver;
 
 impl AocSolver for Day09Solver {
@@ -50,7 +51,7 @@ impl From<usize> for FileId {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
-enum Block {
+pub enum Block {
     File(FileId),
     Free,
 }
@@ -64,17 +65,104 @@ impl std::fmt::Display for Block {
     }
 }
 
-#[derive(Debug)]
-struct BlockStorage {
+/// A single step of `BlockStorage::compact_with` or `BlockStorage::defrag_with`: `size` blocks
+/// of `file` moved from `from` to `to`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Move {
+    pub file: FileId,
+    pub from: usize,
+    pub to: usize,
+    pub size: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockStorage {
     inner: Vec<Block>,
 }
 
 impl BlockStorage {
-    fn iter(&self) -> impl Iterator<Item = &Block> {
+    pub fn iter(&self) -> impl Iterator<Item = &Block> {
         self.into_iter()
     }
 
-    fn compact(&mut self) {
+    /// Returns the extents of each file, from left to right.
+    pub fn files(&self) -> BTreeMap<FileId, Vec<FileEntry>> {
+        let mut files: BTreeMap<FileId, Vec<FileEntry>> = BTreeMap::default();
+        for file in self.iter_files() {
+            if let Block::File(id) = self.inner[file.at] {
+                files.entry(id).or_default().push(file);
+            }
+        }
+        for extents in files.values_mut() {
+            extents.reverse();
+        }
+        files
+    }
+
+    /// Returns the fraction of files that are split into more than one extent.
+    pub fn fragmentation(&self) -> f64 {
+        let files = self.files();
+        if files.is_empty() {
+            return 0.0;
+        }
+        let fragmented = files.values().filter(|extents| extents.len() > 1).count();
+        fragmented as f64 / files.len() as f64
+    }
+
+    /// Encodes the storage as a dense disk map.
+    ///
+    /// Returns `None` when the layout can't be described by a disk map: files must be whole, in
+    /// order of their ids starting from 0, and no file or free span may be larger than 9 blocks.
+    pub fn to_disk_map(&self) -> Option<String> {
+        let mut disk_map = String::default();
+        let mut free = 0;
+        let mut next_id = 0;
+        for (block, _, size) in self.iter_runs() {
+            match block {
+                Block::Free => free = size,
+                Block::File(FileId(id)) => {
+                    // a file that isn't next must have been preceded by empty files
+                    while next_id < id {
+                        if next_id > 0 {
+                            disk_map.push(digit(free)?);
+                            free = 0;
+                        }
+                        disk_map.push('0');
+                        next_id += 1;
+                    }
+                    if next_id != id {
+                        return None;
+                    }
+                    if id > 0 {
+                        disk_map.push(digit(free)?);
+                    }
+                    disk_map.push(digit(size)?);
+                    free = 0;
+                    next_id += 1;
+                }
+            }
+        }
+        if free > 0 {
+            // trailing free space needs an empty file to close the map
+            disk_map.push(digit(free)?);
+            disk_map.push('0');
+        }
+        Some(disk_map)
+    }
+
+    pub fn compact(&mut self) {
+        self.compact_with(|_| ());
+    }
+
+    /// Same as `compact`, calling `observer` with each block that is moved.
+    pub fn compact_with<F>(&mut self, mut observer: F)
+    where
+        F: FnMut(Move),
+    {
+        if !self.inner.contains(&Block::Free) {
+            return;
+        }
+
         // start the "free space index" at the first available free space
         let mut free_space_idx = {
             let mut i = 0;
@@ -94,6 +182,14 @@ impl BlockStorage {
             if free_space_idx >= block_idx {
                 break;
             }
+            if let Block::File(file) = self.inner[block_idx] {
+                observer(Move {
+                    file,
+                    from: block_idx,
+                    to: free_space_idx,
+                    size: 1,
+                });
+            }
             self.inner.swap(free_space_idx, block_idx);
 
             // increment the free space index until we find the next free space
@@ -116,33 +212,84 @@ impl BlockStorage {
         }
     }
 
-    #[inline(always)]
-    fn iter_free_space(&self, leftmost_index: Arc<AtomicUsize>) -> FreeSpaceIterator {
-        FreeSpaceIterator {
-            reported_leftmost_index: false,
-            current_index: leftmost_index.load(Ordering::Relaxed),
-            leftmost_index,
-            storage: self,
-        }
+    /// Returns each run of identical blocks, with where it starts and its length
+    fn iter_runs(&self) -> impl Iterator<Item = (Block, usize, usize)> + '_ {
+        self.inner.chunk_by(|a, b| a == b).scan(0, |at, run| {
+            let start = *at;
+            *at += run.len();
+            Some((run[0], start, run.len()))
+        })
     }
 
-    fn iter_files(&self) -> FileIterator {
-        FileIterator {
-            current_index: self.inner.len() - 1,
-            storage: self,
-        }
+    /// Returns each run of free space, from left to right
+    fn iter_free_space(&self) -> impl Iterator<Item = FreeSpaceEntry> + '_ {
+        self.iter_runs()
+            .filter(|(block, _, _)| *block == Block::Free)
+            .map(|(_, at, size)| FreeSpaceEntry { at, size })
+    }
+
+    /// Returns each file, from right to left
+    fn iter_files(&self) -> impl Iterator<Item = FileEntry> + '_ {
+        self.inner
+            .chunk_by(|a, b| a == b)
+            .rev()
+            .scan(self.inner.len(), |end, run| {
+                *end -= run.len();
+                Some((run[0], *end, run.len()))
+            })
+            .filter(|(block, _, _)| *block != Block::Free)
+            .map(|(_, at, size)| FileEntry { at, size })
     }
 
-    fn defrag(&mut self) {
+    pub fn defrag(&mut self) {
+        self.defrag_with(|_| ());
+    }
+
+    /// Same as `defrag`, calling `observer` with each file that is moved.
+    pub fn defrag_with<F>(&mut self, mut observer: F)
+    where
+        F: FnMut(Move),
+    {
+        // Free spans are kept in one min-heap per size, ordered by position. Spans at least as
+        // large as the largest file share the last heap. Moving a file then only needs to check
+        // the front of each heap that is large enough.
+        let mut free_space: [BinaryHeap<Reverse<FreeSpaceEntry>>; MAX_FILE_SIZE + 1] =
+            Default::default();
+        for space in self.iter_free_space() {
+            free_space[space.size.min(MAX_FILE_SIZE)].push(Reverse(space));
+        }
+
+        // Space freed by a moved file is never reused: every file still to move is further left.
         let files = self.iter_files().collect::<Vec<_>>();
-        let leftmost_index = Arc::new(AtomicUsize::from(0));
         for file in files {
-            let mut free_space = self.iter_free_space(Arc::clone(&leftmost_index));
-            if let Some(space) = free_space.find(|space| file.size() <= space.size()) {
-                if space.index() >= file.index() {
-                    continue;
-                }
-                self.swap_chunk(space, file);
+            let leftmost = free_space[file.size.min(MAX_FILE_SIZE)..]
+                .iter()
+                .filter_map(|heap| heap.peek().map(|Reverse(space)| *space))
+                .min();
+            let Some(space) = leftmost else {
+                continue;
+            };
+            if space.index() >= file.index() {
+                continue;
+            }
+
+            free_space[space.size.min(MAX_FILE_SIZE)].pop();
+            if let Block::File(id) = self.inner[file.at] {
+                observer(Move {
+                    file: id,
+                    from: file.at,
+                    to: space.at,
+                    size: file.size,
+                });
+            }
+            self.swap_chunk(space, file);
+
+            let remaining = FreeSpaceEntry {
+                at: space.at + file.size,
+                size: space.size - file.size,
+            };
+            if remaining.size > 0 {
+                free_space[remaining.size.min(MAX_FILE_SIZE)].push(Reverse(remaining));
             }
         }
     }
@@ -157,14 +304,9 @@ impl BlockStorage {
             self.inner.swap(space.index() + s, file.index() + s);
         }
     }
-
-    #[inline(always)]
-    pub fn len(&self) -> usize {
-        self.inner.len()
-    }
 }
 
-trait BlockInfo {
+pub trait BlockInfo {
     fn index(&self) -> usize;
     fn size(&self) -> usize;
 }
@@ -187,61 +329,9 @@ impl BlockInfo for FreeSpaceEntry {
     }
 }
 
-#[derive(Debug)]
-struct FreeSpaceIterator<'a> {
-    current_index: usize,
-    reported_leftmost_index: bool,
-    leftmost_index: Arc<AtomicUsize>,
-    storage: &'a BlockStorage,
-}
-
-impl<'a> Iterator for FreeSpaceIterator<'a> {
-    type Item = FreeSpaceEntry;
-
-    #[inline(always)]
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut i = self.current_index;
-        if i == self.storage.len() - 1 {
-            return None;
-        }
-        // skip file blocks
-        while self.storage[i] != Block::Free {
-            i += 1;
-            self.current_index += 1;
-            if i == self.storage.len() - 1 {
-                break;
-            }
-        }
-        let free_space_start = i;
-
-        // crappy optimization so we start on the next free space when the iterator is created
-        // again
-        if !self.reported_leftmost_index {
-            self.leftmost_index
-                .store(free_space_start, Ordering::Relaxed);
-            self.reported_leftmost_index = true;
-        }
-
-        let mut size = 0;
-        // track free blocks
-        while self.storage[i] == Block::Free {
-            size += 1;
-            i += 1;
-            self.current_index += 1;
-            if i == self.storage.len() - 1 {
-                break;
-            }
-        }
-
-        (size > 0).then_some(FreeSpaceEntry {
-            at: free_space_start,
-            size,
-        })
-    }
-}
-
+/// A run of blocks belonging to one file.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-struct FileEntry {
+pub struct FileEntry {
     at: usize,
     size: usize,
 }
@@ -258,38 +348,6 @@ impl BlockInfo for FileEntry {
     }
 }
 
-#[derive(Debug)]
-struct FileIterator<'a> {
-    current_index: usize,
-    storage: &'a BlockStorage,
-}
-
-impl<'a> Iterator for FileIterator<'a> {
-    type Item = FileEntry;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut i = self.current_index;
-        // skip free space if we left off on free space on a previous iteration
-        while self.storage[i] == Block::Free {
-            i -= 1;
-            self.current_index -= 1;
-        }
-
-        let current = self.storage[i];
-        let mut size = 0;
-        while self.storage[i] == current {
-            if i == 0 {
-                break;
-            }
-            self.current_index -= 1;
-            size += 1;
-            i -= 1;
-        }
-
-        (size > 0).then_some(FileEntry { at: i + 1, size })
-    }
-}
-
 impl std::fmt::Display for BlockStorage {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         for block in &self.inner {
@@ -361,6 +419,10 @@ impl Index<usize> for BlockStorage {
     }
 }
 
+fn digit(n: usize) -> Option<char> {
+    char::from_digit(u32::try_from(n).ok()?, 10)
+}
+
 fn char_to_num(b: u8) -> u8 {
     match b {
         b'0' => 0,
@@ -418,7 +480,7 @@ mod tests {
     fn iter_free_space() {
         let storage = BlockStorage::from(SAMPLE);
 
-        let mut free_space = storage.iter_free_space(Arc::new(AtomicUsize::from(0)));
+        let mut free_space = storage.iter_free_space();
 
         assert_eq!(free_space.next(), Some(FreeSpaceEntry { at: 2, size: 3 }));
         assert_eq!(free_space.next(), Some(FreeSpaceEntry { at: 8, size: 3 }));
@@ -450,6 +512,87 @@ mod tests {
         );
     }
 
+    #[test]
+    fn defrags_into_space_left_by_empty_files() {
+        let mut storage = BlockStorage::from("150490");
+
+        storage.defrag();
+
+        assert_eq!(storage.to_string(), "0222222222.........");
+    }
+
+    #[test]
+    fn encodes_disk_map() {
+        let storage = BlockStorage::from(SAMPLE);
+        assert_eq!(storage.to_disk_map().as_deref(), Some(SAMPLE.trim_end()));
+
+        let storage = BlockStorage::from("12003");
+        assert_eq!(storage.to_string(), "0..222");
+        assert_eq!(storage.to_disk_map().as_deref(), Some("12003"));
+
+        // free space next to an empty file is given to the file before it
+        let storage = BlockStorage::from("1003120");
+        assert_eq!(storage.to_string(), "0...2..");
+        assert_eq!(storage.to_disk_map().as_deref(), Some("1300120"));
+        assert_eq!(BlockStorage::from("1300120"), storage);
+
+        let mut storage = BlockStorage::from(SAMPLE);
+        storage.compact();
+        assert_eq!(storage.to_disk_map(), None);
+    }
+
+    #[test]
+    fn reports_file_extents() {
+        let mut storage = BlockStorage::from(SAMPLE);
+        assert_eq!(storage.files().len(), 10);
+        assert_eq!(storage.fragmentation(), 0.0);
+
+        storage.compact();
+        let files = storage.files();
+        assert_eq!(
+            files[&FileId(8)],
+            vec![FileEntry { at: 4, size: 1 }, FileEntry { at: 8, size: 3 }]
+        );
+        assert_eq!(files[&FileId(9)], vec![FileEntry { at: 2, size: 2 }]);
+        assert!(storage.fragmentation() > 0.0);
+    }
+
+    #[test]
+    fn observes_moves() {
+        for defrag in [false, true] {
+            let mut storage = BlockStorage::from(SAMPLE);
+            let mut replayed = storage.clone();
+
+            let mut moves = Vec::default();
+            if defrag {
+                storage.defrag_with(|m| moves.push(m));
+            } else {
+                storage.compact_with(|m| moves.push(m));
+            }
+
+            for m in &moves {
+                for i in 0..m.size {
+                    assert_eq!(replayed[m.from + i], Block::File(m.file));
+                    replayed.inner.swap(m.from + i, m.to + i);
+                }
+            }
+            assert_eq!(replayed, storage);
+        }
+
+        let mut moves = Vec::default();
+        BlockStorage::from(SAMPLE).defrag_with(|m| moves.push(m));
+        assert_eq!(
+            moves[0],
+            Move {
+                file: FileId(9),
+                from: 40,
+                to: 2,
+                size: 2
+            }
+        );
+        assert_eq!(moves.len(), 4);
+    }
+
     #[test]
     fn solves_part_2() {
         let answer = Day09Solver::part_2(SAMPLE);
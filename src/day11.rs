@@ -97,7 +97,7 @@ mod rules {
             let digits = num_digits(stone);
 
             // if even
-            if digits % 2 == 0 {
+            if digits.is_multiple_of(2) {
                 let (a, b) = split(stone, digits);
                 Some(smallvec![a, b])
             } else {
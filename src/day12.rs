@@ -2,8 +2,7 @@ use fxhash::FxHashSet;
 use rayon::prelude::*;
 
 use crate::{
-    grid::{Grid2D, Pos2, Query},
-    mask::Mask2D,
+    grid::{region::Region, Grid2D, Pos2, Query},
     AocSolver,
 };
 
@@ -16,8 +15,8 @@ impl AocSolver for Day12Solver {
     // 1. get all plant types (letters)
     // 2. for each plant type, find all the plots (same types that are adjacent). this is done 1
     //    plant type per thread
-    //    - finding the plots uses a recursive nightmare that walks the plants until no more
-    //      adjacent ones are found
+    //    - finding the plots uses a flood fill that walks the plants until no more adjacent ones
+    //      are found
     //    - once a plot is found, a loop removes it from a set of all plants. this is so we know
     //      what other plots need to be walked
     // 3. (in thread): calculate the area and perimeter
@@ -46,10 +45,10 @@ impl AocSolver for Day12Solver {
 
     // Strategy:
     // 1. do part one up until the perimeter calculation
-    // 2. create a mask from each plot
-    // 3. compare each plant in the plot mask with a known pattern that identifies a corner
-    // 4. for each matched corner, increment the "sides count" by 1
-    // 5. sum the results
+    // 2. create a region from each plot
+    // 3. count the corners of the region. each corner starts a new side, so the number of corners
+    //    is the number of sides
+    // 4. sum the results
     fn part_2(input: &str) -> Self::Output {
         let garden = Garden::from(input);
         garden
@@ -61,8 +60,7 @@ impl AocSolver for Day12Solver {
                     .iter()
                     .map(|plot| {
                         let area = plot.area();
-                        let mask = Mask2D::from_positions(&plot.plants, 1);
-                        let sides = find_sides(plot, mask);
+                        let sides = plot.sides();
                         area * sides
                     })
                     .sum::<u64>()
@@ -71,150 +69,6 @@ impl AocSolver for Day12Solver {
     }
 }
 
-fn find_sides(plot: &Plot, mask: Mask2D) -> u64 {
-    let mut sides = 0;
-    for plant in plot {
-        sides += Corner1.n_corners(*plant, &mask); // 2
-        sides += Corner2.n_corners(*plant, &mask); // 3
-        sides += Corner3.n_corners(*plant, &mask); // 2
-        sides += Corner4.n_corners(*plant, &mask); // 3
-    }
-    sides
-}
-
-trait CornerFinder {
-    fn n_corners(&self, plant: Pos2, mask: &Mask2D) -> u64;
-}
-
-struct Corner1;
-struct Corner2;
-struct Corner3;
-struct Corner4;
-
-impl CornerFinder for Corner1 {
-    #[rustfmt::skip]
-    fn n_corners(&self, plant: Pos2, mask: &Mask2D) -> u64 {
-        // x x
-        // . x
-        let coords = [(-1, 0), (-1, 1), (0, 1)];
-
-        let (this, a, b, c) = extract_submask(mask, plant, coords);
-
-        // corner in top-right with no adjacent plants
-        let pat1 = match (a, b, this, c) {
-            (0, _,
-             _, 0) => 1,
-
-            _ => 0
-        };
-
-        // corner in top-right with adjacent plants
-        let pat2 = match (a, b, this, c) {
-            (1, 0,
-             _, 1) => 1,
-
-            _ => 0
-        };
-
-        pat1 + pat2
-    }
-}
-
-impl CornerFinder for Corner2 {
-    #[rustfmt::skip]
-    fn n_corners(&self, plant: Pos2, mask: &Mask2D) -> u64 {
-        // x x
-        // x .
-        let coords = [(0, -1), (-1, -1), (-1, 0)];
-
-        let (this, a, b, c) = extract_submask(mask, plant, coords);
-
-        // corner in top-left with no adjacent plants
-        let pat1 = match (b, c, a, this) {
-            (_, 0,
-             0, _) => 1,
-
-            _ => 0
-        };
-
-        // corner in top-left with adjacent plants
-        let pat2 = match (b, c, a, this) {
-            (0, 1,
-             1, _) => 1,
-
-            _ => 0
-        };
-
-        pat1 + pat2
-    }
-}
-
-impl CornerFinder for Corner3 {
-    #[rustfmt::skip]
-    fn n_corners(&self, plant: Pos2, mask: &Mask2D) -> u64 {
-        // x .
-        // x x
-        let coords = [(1, 0), (1, -1), (0, -1)];
-
-        let (this, a, b, c) = extract_submask(mask, plant, coords);
-
-        // corner in bottom-left with no adjacent plants
-        let pat1 = match (c, this, b, a) {
-            (0, _,
-             _, 0) => 1,
-
-            _ => 0
-        };
-
-        // corner in bottom-left with adjacent plants
-        let pat2 = match (c, this, b, a) {
-            (1, _,
-             0, 1) => 1,
-
-            _ => 0
-        };
-
-        pat1 + pat2
-    }
-}
-
-impl CornerFinder for Corner4 {
-    #[rustfmt::skip]
-    fn n_corners(&self, plant: Pos2, mask: &Mask2D) -> u64 {
-        // . x
-        // x x
-        let coords = [(0, 1), (1, 1), (1, 0)];
-
-        let (this, a, b, c) = extract_submask(mask, plant, coords);
-
-        // corner in bottom-right with no adjacent plants
-        let pat1 = match (this, a, c, b) {
-            (_, 0,
-             0, _) => 1,
-
-            _ => 0
-        };
-
-        // corner in bottom-right with adjacent plants
-        let pat2 = match (this, a, c, b) {
-            (_, 1,
-             1, 0) => 1,
-
-            _ => 0
-        };
-
-        pat1 + pat2
-    }
-}
-
-fn extract_submask(mask: &Mask2D, plant: Pos2, coords: [(isize, isize); 3]) -> (u8, u8, u8, u8) {
-    let this = mask[plant];
-    let a = mask[plant + Pos2::from(coords[0])];
-    let b = mask[plant + Pos2::from(coords[1])];
-    let c = mask[plant + Pos2::from(coords[2])];
-    (this, a, b, c)
-}
-
 type Plant = char;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -236,6 +90,10 @@ impl Plot {
             .map(|pos| garden.query(Fencing { plant: self.kind }, *pos))
             .sum()
     }
+
+    pub fn sides(&self) -> u64 {
+        Region::new(self.plants.iter().copied()).sides() as u64
+    }
 }
 
 impl<'a> IntoIterator for &'a Plot {
@@ -263,7 +121,7 @@ struct Garden {
 impl Garden {
     // Get all plots for the specified plant
     fn find_plots(&self, plant: Plant) -> Vec<Plot> {
-        use grid_query::{find_plot_impl, PlantKind};
+        use grid_query::PlantKind;
 
         // get coordinates of all of the target plant
         let mut all_plants: FxHashSet<Pos2> = self
@@ -275,37 +133,20 @@ impl Garden {
         // contains all plots for this plant
         let mut plots = Vec::default();
 
-        loop {
-            // get an available plant coordinate
-            let next = all_plants.iter().next().cloned();
+        // get an available plant coordinate
+        while let Some(next) = all_plants.iter().next().copied() {
+            // the plot containing the `next` coordinate is located and saved into `plot`
+            let plot = self.garden.flood_fill(next, |other| *other == plant);
 
-            if let Some(next) = next {
-                // the plot containing the `next` coordinate is located and saved into `plot`
-                let mut plot: FxHashSet<Pos2> = FxHashSet::default();
-                find_plot_impl(&self.garden, &mut plot, plant, next);
-
-                {
-                    // when the length of the all_plants set doesn't change, then that means we are
-                    // done.
-                    let current_len = all_plants.len();
-
-                    // we remove the found plot from all known plant coordinates
-                    all_plants = all_plants.symmetric_difference(&plot).copied().collect();
-
-                    // bail if we the set size didn't change (all plants already visited)
-                    if all_plants.len() == current_len {
-                        break;
-                    }
-                }
-
-                plots.push(Plot {
-                    plants: plot.into_iter().collect(),
-                    kind: plant,
-                });
-            } else {
-                // all plots founds
-                break;
+            // we remove the found plot from all known plant coordinates
+            for pos in &plot {
+                all_plants.remove(pos);
             }
+
+            plots.push(Plot {
+                plants: plot,
+                kind: plant,
+            });
         }
 
         plots
@@ -325,53 +166,7 @@ impl Garden {
 
 mod grid_query {
 
-    use super::Plant;
     use crate::grid::{Direction, Finder, Grid2D, Pos2, Query};
-    use fxhash::FxHashSet;
-    use smallvec::SmallVec;
-
-    pub struct AdjacentPlant(pub Plant);
-
-    impl Query for AdjacentPlant {
-        type Output = SmallVec<[Pos2; 4]>;
-
-        fn query(&mut self, grid: &Grid2D, pos: Pos2) -> Self::Output {
-            let mut adjacent = SmallVec::default();
-
-            // up, down, left, right
-            for direction in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-                let check_pos = pos + Direction::from(direction);
-                if let Some(next) = grid.get(check_pos) {
-                    if next == self.0 {
-                        adjacent.push(check_pos);
-                    }
-                }
-            }
-
-            adjacent
-        }
-    }
-
-    /// Recursive plot finder.
-    pub fn find_plot_impl(
-        grid: &Grid2D,
-        visited: &mut FxHashSet<Pos2>,
-        plant: char,
-        current: Pos2,
-    ) {
-        let adjacent = grid.query(AdjacentPlant(plant), current);
-
-        if adjacent.is_empty() {
-            visited.insert(current);
-            return;
-        }
-
-        for pos in adjacent {
-            if visited.insert(pos) {
-                find_plot_impl(grid, visited, plant, pos);
-            }
-        }
-    }
 
     /// Grid query to calculate the fencing of a specific position.
     pub struct Fencing {
@@ -1,8 +1,14 @@
 use fxhash::FxHashMap;
 use regex::Regex;
-use thread_manager::ThreadManager;
 
-use crate::{wrap::WrappingI64, AocSolver};
+use std::str::FromStr;
+
+use crate::{
+    grid::{render::Cell, Dimensions2D, Grid2D, Pos2, Rect},
+    params::{ParamError, Params},
+    wrap::{crt, extended_gcd, WrappingI64},
+    AocSolver,
+};
 
 pub struct Day14Solver;
 
@@ -10,97 +16,81 @@ impl AocSolver for Day14Solver {
     type Output = usize;
 
     fn part_1(input: &str) -> Self::Output {
-        #[cfg(debug_assertions)]
-        let dimensions = (7, 11);
+        Self::part_1_with_params(input, &Params::default()).expect("no params to check")
+    }
 
-        #[cfg(not(debug_assertions))]
-        let dimensions = (103, 101);
+    // Strategy:
+    // The picture packs the bots together, so look for the frame where the bots are least spread
+    // out. See `PictureSignal` for the ways of measuring that.
+    fn part_2(input: &str) -> Self::Output {
+        Self::part_2_with_params(input, &Params::default()).expect("no params to check")
+    }
 
-        let mut bots = SecurityTeam::new(input, dimensions);
+    /// Params:
+    /// - `rows`, `cols`: size of the map. Inferred from the input when missing.
+    fn part_1_with_params(input: &str, params: &Params) -> Result<Self::Output, ParamError> {
+        let mut bots = SecurityTeam::new(input, dimensions(input, params)?);
         bots.timeshift(100);
 
-        bots.quadrants()
-            .into_iter()
-            .map(|quadrant| bots.in_quadrant(quadrant))
-            .product()
+        Ok(bots.safety_factor())
     }
 
-    // Strategy:
-    // Spawn some threads to check the map. Map is scanned by looking for contiguous entries of
-    // bots (the border). Once we find enough in a row, then we have the pattern.
-    fn part_2(_: &str) -> Self::Output {
-        let input = include_str!("../data/day14.txt");
-        const DIMENSIONS: (i64, i64) = (103, 101);
-        const MAX_PATTERN_ROW: usize = DIMENSIONS.0 as usize - 33;
-
-        let thread_manager =
-            ThreadManager::<()>::new(num_cpus::get().saturating_sub(1).clamp(1, usize::MAX));
-
-        let (tx, rx) = kanal::unbounded();
-
-        for i in 0..(DIMENSIONS.0 * DIMENSIONS.1) {
-            let tx = tx.clone();
-
-            thread_manager.execute(move || {
-                let mut bots = SecurityTeam::new(input, DIMENSIONS);
-                bots.timeshift(i);
-
-                let map = bots.make_lazy_2d_bot_map();
-                for (n_row, row) in map.iter().enumerate() {
-                    // patern is 33 units high, so if we get near the bottom then it won't fit
-                    // anymore. bail
-                    if n_row > MAX_PATTERN_ROW {
-                        break;
-                    }
-                    let mut contiguous = 0;
-                    for col in row {
-                        match col {
-                            1 => contiguous += 1,
-                            _ => contiguous = 0,
-                        }
-                        if contiguous > 8 {
-                            let _ = tx.send(i);
-                        }
-                    }
-                }
-            });
-        }
-        match rx.recv() {
-            Ok(seconds) => seconds as usize,
-            Err(_) => panic!("recv error"),
-        }
+    /// Params:
+    /// - `rows`, `cols`: size of the map. Inferred from the input when missing.
+    /// - `signal`: `variance` (default), `safety` or `entropy`. See `PictureSignal`.
+    fn part_2_with_params(input: &str, params: &Params) -> Result<Self::Output, ParamError> {
+        let bots = SecurityTeam::new(input, dimensions(input, params)?);
+        let signal = params.get("signal")?.unwrap_or_default();
+        Ok(bots.find_picture(signal) as usize)
     }
 }
 
-type Wi64 = WrappingI64;
-type Pos = (i64, i64);
-type Total = usize;
-type BotPositions = FxHashMap<Pos, Total>;
-type BotMap2d = Vec<Vec<u8>>;
-
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Ord, Eq)]
-pub struct Rect {
-    top_left: Pos,
-    bottom_right: Pos,
+/// Map sizes used by the puzzle: the example and the real input.
+const KNOWN_DIMENSIONS: [(i64, i64); 2] = [(7, 11), (103, 101)];
+
+/// Returns the `rows` and `cols` params, inferring whichever is missing from the input.
+pub fn dimensions(input: &str, params: &Params) -> Result<(i64, i64), ParamError> {
+    let (rows, cols) = SecurityTeam::infer_dimensions(input);
+    let size = |key, inferred| match params.get::<i64>(key)? {
+        Some(size) if size <= 0 => Err(ParamError::new(key, &size.to_string(), "must be positive")),
+        size => Ok(size.unwrap_or(inferred)),
+    };
+    Ok((size("rows", rows)?, size("cols", cols)?))
 }
 
-impl Rect {
-    #[rustfmt::skip]
-    fn contains(&self, pos: Pos) -> bool {
-        pos.0 >= self.top_left.0 && pos.0 <= self.bottom_right.0  // row
-            && pos.1 >= self.top_left.1 && pos.1 <= self.bottom_right.1 // col
-    }
+/// How to tell that the bots have formed a picture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PictureSignal {
+    /// Minimum variance of the positions, per axis.
+    ///
+    /// Rows and columns move independently and repeat every `rows` and `cols` seconds, so the
+    /// time of least row variance and the time of least column variance are found separately
+    /// and then combined with the chinese remainder theorem. Only `rows + cols` frames are
+    /// checked.
+    #[default]
+    Variance,
+    /// Minimum safety factor. Works when the picture sits mostly within one quadrant.
+    SafetyFactor,
+    /// Minimum entropy of the bot counts over blocks of the map.
+    Entropy,
 }
 
-impl From<(Pos, Pos)> for Rect {
-    fn from((top_left, bottom_right): (Pos, Pos)) -> Self {
-        Self {
-            top_left,
-            bottom_right,
+impl FromStr for PictureSignal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "variance" => Ok(PictureSignal::Variance),
+            "safety" => Ok(PictureSignal::SafetyFactor),
+            "entropy" => Ok(PictureSignal::Entropy),
+            _ => Err(format!("unknown signal {s:?}")),
         }
     }
 }
-#[derive(Debug)]
+
+type Wi64 = WrappingI64;
+
+#[derive(Debug, Clone)]
 struct Bot {
     pos: (Wi64, Wi64),
     velocity: (i64, i64),
@@ -109,18 +99,22 @@ struct Bot {
 impl Bot {
     #[inline(always)]
     fn timeshift(&mut self, seconds: i64) {
-        let velocity = (self.velocity.0 * seconds, self.velocity.1 * seconds);
-        self.pos.0 += velocity.0;
-        self.pos.1 += velocity.1;
+        self.pos.0 = self.pos.0.add_scaled(self.velocity.0, seconds);
+        self.pos.1 = self.pos.1.add_scaled(self.velocity.1, seconds);
     }
 
     #[inline(always)]
     fn pos_as_i64(&self) -> (i64, i64) {
         (self.pos.0.as_i64(), self.pos.1.as_i64())
     }
+
+    #[inline(always)]
+    fn pos2(&self) -> Pos2 {
+        Pos2::new(self.pos.0.as_i64() as isize, self.pos.1.as_i64() as isize)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SecurityTeam {
     inner: Vec<Bot>,
     rows: i64,
@@ -135,123 +129,259 @@ impl SecurityTeam {
     }
 
     pub fn new(input: &str, (rows, cols): (i64, i64)) -> Self {
-        let re_bot = Regex::new(r#"p=(-?\d*),(-?\d*) v=(-?\d*),(-?\d*)"#).unwrap();
         SecurityTeam {
-            inner: {
-                input
-                    .lines()
-                    .map(|line| {
-                        let caps = re_bot.captures(line).unwrap();
-                        let (_, [col, row, v_col, v_row]) = caps.extract();
-                        Bot {
-                            pos: (
-                                Wi64::new(row.to_i64(), (0, rows - 1)),
-                                Wi64::new(col.to_i64(), (0, cols - 1)),
-                            ),
-                            velocity: (v_row.to_i64(), v_col.to_i64()),
-                        }
-                    })
-                    .collect()
-            },
+            inner: parse_bots(input)
+                .map(|((row, col), velocity)| Bot {
+                    // starting positions can be outside a map given as params
+                    pos: (
+                        Wi64::new(0, (0, rows - 1)) + row,
+                        Wi64::new(0, (0, cols - 1)) + col,
+                    ),
+                    velocity,
+                })
+                .collect(),
             rows,
             cols,
         }
     }
 
+    /// Returns the smallest known map size that fits every starting position, or just enough
+    /// rows and columns to fit them if none do.
+    pub fn infer_dimensions(input: &str) -> (i64, i64) {
+        let (rows, cols) = parse_bots(input).fold((0, 0), |(rows, cols), ((row, col), _)| {
+            (rows.max(row + 1), cols.max(col + 1))
+        });
+
+        KNOWN_DIMENSIONS
+            .into_iter()
+            .find(|(known_rows, known_cols)| rows <= *known_rows && cols <= *known_cols)
+            .unwrap_or((rows, cols))
+    }
+
     pub fn timeshift(&mut self, seconds: i64) {
         self.inner.iter_mut().for_each(|bot| {
             bot.timeshift(seconds);
         });
     }
 
-    /// Returns a hashmap that contains bot positions and the number per position
-    fn calculate_bot_position_counts(&self) -> BotPositions {
-        let mut bots: FxHashMap<(i64, i64), usize> = FxHashMap::default();
-        for bot in self.inner.iter() {
-            let entry = bots.entry(bot.pos_as_i64()).or_default();
-            *entry += 1;
+    /// Returns the number of seconds until every bot is back where it started
+    pub fn period(&self) -> i64 {
+        self.rows * self.cols / extended_gcd(self.rows, self.cols).0
+    }
+
+    /// Returns the `(rows, cols)` of the map
+    pub fn dimensions(&self) -> (i64, i64) {
+        (self.rows, self.cols)
+    }
+
+    /// Returns the position and velocity of each bot, as `(row, col)`
+    pub fn iter_bots(&self) -> impl Iterator<Item = (Pos2, Pos2)> + '_ {
+        self.inner.iter().map(|bot| {
+            let velocity = Pos2::new(bot.velocity.0 as isize, bot.velocity.1 as isize);
+            (bot.pos2(), velocity)
+        })
+    }
+
+    pub fn iter_positions(&self) -> impl Iterator<Item = Pos2> + '_ {
+        self.inner.iter().map(Bot::pos2)
+    }
+
+    /// Returns where the bot at `index` will be after `seconds`, without moving it
+    pub fn position_at(&self, index: usize, seconds: i64) -> Pos2 {
+        let mut bot = self.inner[index].clone();
+        bot.timeshift(seconds);
+        bot.pos2()
+    }
+
+    /// Returns where every bot will be after `seconds`, without moving them
+    pub fn positions_at(&self, seconds: i64) -> impl Iterator<Item = Pos2> + '_ {
+        self.inner.iter().map(move |bot| {
+            let mut bot = bot.clone();
+            bot.timeshift(seconds);
+            bot.pos2()
+        })
+    }
+
+    /// Returns a grid with the number of bots on each tile
+    pub fn density_map(&self) -> Grid2D<usize> {
+        let mut map = Grid2D::from_fn(
+            Dimensions2D::new(self.rows as usize, self.cols as usize),
+            |_| 0,
+        );
+        for pos in self.iter_positions() {
+            map[pos] += 1;
         }
-        bots
+        map
     }
 
-    /// Create a 2d map where each position has a number indicating the total number of bots
-    fn make_2d_bot_map(&self) -> BotMap2d {
-        let mut buf = (0..self.rows as usize)
-            .map(|_| vec![0; self.cols as usize])
-            .collect::<Vec<_>>();
+    /// Returns the number of bots in the largest group of occupied tiles that touch (up, down,
+    /// left, right)
+    pub fn largest_cluster(&self) -> usize {
+        let map = self.density_map();
+        map.components(|a, b| (*a > 0) == (*b > 0))
+            .regions()
+            .iter()
+            .map(|region| region.cells().iter().map(|pos| map[*pos]).sum())
+            .max()
+            .unwrap_or(0)
+    }
 
-        let positions = self.calculate_bot_position_counts();
+    /// Returns the fraction of bots that share a tile with, or are next to (up, down, left,
+    /// right), another bot
+    pub fn clustered_fraction(&self) -> f64 {
+        let map = self.density_map();
+        let clustered = self
+            .iter_positions()
+            .filter(|pos| map[*pos] > 1 || map.neighbours(*pos).any(|next| map[next] > 0))
+            .count();
+        clustered as f64 / self.inner.len() as f64
+    }
 
-        for ((row, col), total) in positions {
-            buf[row as usize][col as usize] = total as u8;
+    /// Returns the variance of the bot rows and of the bot columns
+    pub fn variance(&self) -> (f64, f64) {
+        let n = self.inner.len() as f64;
+        let (mut sum, mut sum_sq) = ((0.0, 0.0), (0.0, 0.0));
+        for bot in &self.inner {
+            let (row, col) = bot.pos_as_i64();
+            let (row, col) = (row as f64, col as f64);
+            sum = (sum.0 + row, sum.1 + col);
+            sum_sq = (sum_sq.0 + row * row, sum_sq.1 + col * col);
         }
-        buf
+        let mean = (sum.0 / n, sum.1 / n);
+        (sum_sq.0 / n - mean.0 * mean.0, sum_sq.1 / n - mean.1 * mean.1)
     }
 
-    /// Create a 2d map where each filled position is a 1 and any unfilled position is a 0
-    fn make_lazy_2d_bot_map(&self) -> BotMap2d {
-        let mut buf = (0..self.rows as usize)
-            .map(|_| vec![0; self.cols as usize])
-            .collect::<Vec<_>>();
+    /// Returns the product of the number of bots in each quadrant
+    pub fn safety_factor(&self) -> usize {
+        self.quadrants()
+            .into_iter()
+            .map(|quadrant| self.in_quadrant(quadrant))
+            .product()
+    }
 
+    /// Returns the shannon entropy (in bits) of the bot counts over blocks of the map. Lower
+    /// means the bots are more clustered.
+    pub fn entropy(&self) -> f64 {
+        // roughly 10 blocks along each axis
+        let block = ((self.rows / 10).max(1), (self.cols / 10).max(1));
+        let mut counts: FxHashMap<(i64, i64), usize> = FxHashMap::default();
         for bot in &self.inner {
             let (row, col) = bot.pos_as_i64();
-            buf[row as usize][col as usize] = 1;
+            *counts.entry((row / block.0, col / block.1)).or_default() += 1;
+        }
+
+        let total = self.inner.len() as f64;
+        counts
+            .values()
+            .map(|count| {
+                let p = *count as f64 / total;
+                -p * p.log2()
+            })
+            .sum()
+    }
+
+    /// Returns the number of seconds from now until the bots form a picture, in
+    /// `0..rows * cols` for coprime dimensions.
+    pub fn find_picture(&self, signal: PictureSignal) -> i64 {
+        let (rows, cols) = (self.rows, self.cols);
+        let period = self.period();
+
+        let mut bots = self.clone();
+        match signal {
+            PictureSignal::Variance => {
+                let mut best_row = (f64::MAX, 0);
+                let mut best_col = (f64::MAX, 0);
+                for t in 0..rows.max(cols) {
+                    let (row_variance, col_variance) = bots.variance();
+                    if t < rows && row_variance < best_row.0 {
+                        best_row = (row_variance, t);
+                    }
+                    if t < cols && col_variance < best_col.0 {
+                        best_col = (col_variance, t);
+                    }
+                    bots.timeshift(1);
+                }
+
+                match crt(&[(best_row.1, rows), (best_col.1, cols)]) {
+                    Some((seconds, _)) => seconds,
+                    // the best times can't happen together, so fall back to checking every frame
+                    None => self.find_minimum(period, |bots| {
+                        let (row_variance, col_variance) = bots.variance();
+                        row_variance + col_variance
+                    }),
+                }
+            }
+            PictureSignal::SafetyFactor => {
+                self.find_minimum(period, |bots| bots.safety_factor() as f64)
+            }
+            PictureSignal::Entropy => self.find_minimum(period, SecurityTeam::entropy),
         }
-        buf
+    }
+
+    /// Returns the first time in `0..period` where `score` is lowest.
+    fn find_minimum<F>(&self, period: i64, score: F) -> i64
+    where
+        F: Fn(&SecurityTeam) -> f64,
+    {
+        let mut bots = self.clone();
+        let mut best = (f64::MAX, 0);
+        for t in 0..period {
+            let score = score(&bots);
+            if score < best.0 {
+                best = (score, t);
+            }
+            bots.timeshift(1);
+        }
+        best.1
     }
 
     /// Returns the quadrants of the map
     pub fn quadrants(&self) -> [Rect; 4] {
-        let mid_col = self.cols / 2;
-        let mid_row = self.rows / 2;
+        let (rows, cols) = (self.rows as isize, self.cols as isize);
+        let mid_col = cols / 2;
+        let mid_row = rows / 2;
         [
             // top left
-            Rect {
-                top_left: (0, 0),
-                bottom_right: (mid_row - 1, mid_col - 1),
-            },
+            Rect::from(((0, 0), (mid_row - 1, mid_col - 1))),
             // top right
-            Rect {
-                top_left: (0, mid_col + 1),
-                bottom_right: (mid_row - 1, self.cols - 1),
-            },
+            Rect::from(((0, mid_col + 1), (mid_row - 1, cols - 1))),
             // bottom left
-            Rect {
-                top_left: (mid_row + 1, 0),
-                bottom_right: (self.rows - 1, mid_col - 1),
-            },
+            Rect::from(((mid_row + 1, 0), (rows - 1, mid_col - 1))),
             // bottom right
-            Rect {
-                top_left: (mid_row + 1, mid_col + 1),
-                bottom_right: (self.rows - 1, self.cols - 1),
-            },
+            Rect::from(((mid_row + 1, mid_col + 1), (rows - 1, cols - 1))),
         ]
     }
 
     pub fn in_quadrant(&self, quadrant: Rect) -> usize {
         self.inner
             .iter()
-            .filter(|bot| quadrant.contains(bot.pos_as_i64()))
+            .filter(|bot| quadrant.contains(bot.pos2()))
             .count()
     }
 }
 
 impl std::fmt::Display for SecurityTeam {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let map = self.make_2d_bot_map();
-        for row in map {
-            for col in row {
-                let ch = char::from_digit(col as u32, 10).unwrap();
-                write!(f, "{}", ch).unwrap();
-            }
-            writeln!(f).unwrap();
-        }
-
-        Ok(())
+        // counts above 9 continue with letters (`a` is 10), and `+` means more than 35
+        let map = self.density_map();
+        let rendered = map.render(|_, count| {
+            let ch = char::from_digit(*count as u32, 36).unwrap_or('+');
+            Cell::from(ch)
+        });
+        write!(f, "{rendered}")
     }
 }
 
+/// Returns the `(row, col)` position and velocity of each bot
+fn parse_bots(input: &str) -> impl Iterator<Item = ((i64, i64), (i64, i64))> + '_ {
+    let re_bot = Regex::new(r#"p=(-?\d*),(-?\d*) v=(-?\d*),(-?\d*)"#).unwrap();
+    input.lines().map(move |line| {
+        let caps = re_bot.captures(line).unwrap();
+        let (_, [col, row, v_col, v_row]) = caps.extract();
+        ((row.to_i64(), col.to_i64()), (v_row.to_i64(), v_col.to_i64()))
+    })
+}
+
 trait Toi64 {
     fn to_i64(&self) -> i64;
 }
@@ -264,7 +394,11 @@ impl Toi64 for &str {
 
 #[cfg(test)]
 mod tests {
-    use crate::{day14::SecurityTeam, AocSolver};
+    use crate::{
+        day14::{dimensions, PictureSignal, SecurityTeam},
+        grid::Pos2,
+        AocSolver, Params,
+    };
 
     use super::Day14Solver;
 
@@ -322,6 +456,70 @@ p=9,5 v=-3,-3"#;
         assert_eq!(bots.to_string(), SAMPLE_100);
     }
 
+    #[test]
+    fn predicts_positions_without_moving() {
+        let bots = SecurityTeam::new(SAMPLE, (7, 11));
+        assert_eq!(bots.period(), 77);
+
+        let mut moved = bots.clone();
+        moved.timeshift(100);
+        assert_eq!(
+            bots.positions_at(100).collect::<Vec<_>>(),
+            moved.iter_positions().collect::<Vec<_>>()
+        );
+        assert_eq!(bots.position_at(0, 2), Pos2::new(5, 6));
+        assert_eq!(bots.position_at(0, 2 + bots.period()), Pos2::new(5, 6));
+
+        let (pos, velocity) = bots.iter_bots().next().unwrap();
+        assert_eq!((pos, velocity), (Pos2::new(4, 0), Pos2::new(-3, 3)));
+    }
+
+    #[test]
+    fn maps_bot_density() {
+        let mut bots = SecurityTeam::new(SAMPLE, (7, 11));
+        bots.timeshift(100);
+
+        let map = bots.density_map();
+        assert_eq!(map.positions().map(|pos| map[pos]).sum::<usize>(), bots.len());
+        assert_eq!(map[Pos2::new(5, 4)], 2);
+        assert_eq!(map[Pos2::new(0, 0)], 0);
+    }
+
+    #[test]
+    fn displays_crowded_tiles() {
+        let input = "p=1,0 v=0,0\n".repeat(12) + &"p=2,0 v=0,0\n".repeat(40);
+        let bots = SecurityTeam::new(&input, (1, 4));
+
+        assert_eq!(bots.to_string(), "0c+0\n");
+    }
+
+    #[test]
+    fn measures_clustering() {
+        let input = "p=0,0 v=0,0\np=1,0 v=0,0\np=1,1 v=0,0\np=1,1 v=0,0\np=5,5 v=0,0";
+        let bots = SecurityTeam::new(input, (7, 11));
+
+        assert_eq!(bots.largest_cluster(), 4);
+        assert_eq!(bots.clustered_fraction(), 0.8);
+
+        let (row_variance, col_variance) = bots.variance();
+        assert!(row_variance > 0.0 && col_variance > 0.0);
+    }
+
+    #[test]
+    fn timeshifts_any_number_of_seconds() {
+        let mut bots = SecurityTeam::new(SAMPLE, (7, 11));
+        bots.timeshift(i64::MAX);
+
+        // positions repeat every 7 * 11 seconds
+        let mut expected = SecurityTeam::new(SAMPLE, (7, 11));
+        expected.timeshift(i64::MAX % 77);
+        assert_eq!(bots.to_string(), expected.to_string());
+
+        bots.timeshift(i64::MIN);
+        expected.timeshift(i64::MIN % 77);
+        assert_eq!(bots.to_string(), expected.to_string());
+    }
+
     #[test]
     fn gets_quadrants() {
         let mut bots = SecurityTeam::new(SAMPLE, (7, 11));
@@ -362,6 +560,121 @@ p=9,5 v=-3,-3"#;
         assert_eq!(safety_factor, 12);
     }
 
+    /// Makes bots on a `rows` × `cols` map that form a filled square in the top left quadrant after
+    /// `seconds`, with as many bots scattered around as noise.
+    fn planted_picture((rows, cols): (i64, i64), seconds: i64) -> String {
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+        let mut random = |n: i64| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed % n as u64) as i64
+        };
+
+        let mut lines = Vec::default();
+        for i in 0..200 {
+            let (row, col) = if i < 100 {
+                (rows / 8 + i / 10, cols / 8 + i % 10)
+            } else {
+                (random(rows), random(cols))
+            };
+            let velocity = (random(41) - 20, random(41) - 20);
+            let start = (
+                (row - velocity.0 * seconds).rem_euclid(rows),
+                (col - velocity.1 * seconds).rem_euclid(cols),
+            );
+            lines.push(format!(
+                "p={},{} v={},{}",
+                start.1, start.0, velocity.1, velocity.0
+            ));
+        }
+        lines.join("\n")
+    }
+
+    #[test]
+    fn finds_picture_with_every_signal() {
+        let dimensions = (43, 47);
+        let bots = SecurityTeam::new(&planted_picture(dimensions, 1234), dimensions);
+
+        assert_eq!(bots.find_picture(PictureSignal::Variance), 1234);
+        assert_eq!(bots.find_picture(PictureSignal::SafetyFactor), 1234);
+        assert_eq!(bots.find_picture(PictureSignal::Entropy), 1234);
+    }
+
+    #[test]
+    fn finds_picture_with_shared_factor_dimensions() {
+        let dimensions = (40, 30);
+        let bots = SecurityTeam::new(&planted_picture(dimensions, 77), dimensions);
+
+        assert_eq!(bots.find_picture(PictureSignal::Variance), 77);
+    }
+
+    #[test]
+    fn infers_dimensions() {
+        assert_eq!(SecurityTeam::infer_dimensions(SAMPLE), (7, 11));
+        assert_eq!(
+            SecurityTeam::infer_dimensions("p=0,0 v=1,1\np=100,50 v=1,1"),
+            (103, 101)
+        );
+        assert_eq!(
+            SecurityTeam::infer_dimensions("p=200,0 v=1,1\np=3,9 v=1,1"),
+            (10, 201)
+        );
+    }
+
+    #[test]
+    fn dimensions_can_be_given_as_params() {
+        let params = Params::new().with("rows", 9).with("cols", 13);
+        let mut bots = SecurityTeam::new(SAMPLE, dimensions(SAMPLE, &params).unwrap());
+        assert_eq!(bots.quadrants()[3], ((5, 7), (8, 12)).into());
+
+        bots.timeshift(100);
+        assert_eq!(
+            Day14Solver::part_1_with_params(SAMPLE, &params),
+            Ok(bots.safety_factor())
+        );
+    }
+
+    #[test]
+    fn wraps_starting_positions_onto_smaller_maps() {
+        let bots = SecurityTeam::new(SAMPLE, (3, 5));
+
+        assert_eq!(bots.position_at(0, 0), Pos2::new(1, 0));
+        assert_eq!(bots.position_at(2, 0), Pos2::new(0, 0));
+        assert!(bots
+            .positions_at(0)
+            .all(|pos| (0..3).contains(&pos.row) && (0..5).contains(&pos.col)));
+        let map = bots.density_map();
+        assert_eq!(map.positions().map(|pos| map[pos]).sum::<usize>(), 12);
+        assert_eq!(bots.to_string().lines().count(), 3);
+
+        let params = Params::new().with("rows", 3).with("cols", 5);
+        assert!(Day14Solver::part_1_with_params(SAMPLE, &params).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_params() {
+        for (key, value) in [("rows", "abc"), ("cols", "0"), ("signal", "loudest")] {
+            let params = Params::new().with(key, value);
+            let error = Day14Solver::part_2_with_params(SAMPLE, &params).unwrap_err();
+            assert_eq!(error.key(), key);
+        }
+    }
+
+    #[test]
+    fn solves_part_2_with_params() {
+        let dimensions = (43, 47);
+        let input = planted_picture(dimensions, 1234);
+
+        for signal in ["variance", "safety", "entropy"] {
+            let params = Params::new()
+                .with("rows", dimensions.0)
+                .with("cols", dimensions.1)
+                .with("signal", signal);
+            assert_eq!(Day14Solver::part_2_with_params(&input, &params), Ok(1234));
+        }
+    }
+
     #[test]
     fn solves_part_1() {
         let answer = Day14Solver::part_1(SAMPLE);
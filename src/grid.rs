@@ -1,18 +1,45 @@
+mod component;
 mod direction;
+mod grid3d;
+pub mod image;
+pub mod path;
+pub mod pattern;
 mod position;
+mod ray;
+mod rect;
+pub mod region;
+pub mod render;
+mod sparse;
+mod view;
 
-use std::ops::Index;
+use std::{
+    hash::Hash,
+    ops::{Index, IndexMut},
+};
 
+pub use component::{Component, Components};
 pub use direction::Direction;
 use fxhash::FxHashSet;
-pub use position::Pos2;
+pub use grid3d::{Connectivity, Dimensions3D, Grid3D};
+pub use position::{Pos2, Pos3};
+pub use ray::{Line, Ray};
+pub use rect::Rect;
+pub use sparse::SparseGrid;
+pub use view::GridView;
 
+use crate::wrap::WrappingI64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Dimensions2D {
     rows: usize,
     cols: usize,
 }
 
 impl Dimensions2D {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self { rows, cols }
+    }
+
     #[inline(always)]
     pub fn rows(&self) -> usize {
         self.rows
@@ -24,44 +51,123 @@ impl Dimensions2D {
     }
 }
 
-pub trait Finder: Clone {
-    fn check(&self, ch: char) -> bool;
+pub trait Finder<T = char>: Clone {
+    fn check(&self, ch: T) -> bool;
 }
 
-pub trait Query {
+pub trait Query<G = Grid2D, P = Pos2> {
     type Output;
-    fn query(&mut self, grid: &Grid2D, pos: Pos2) -> Self::Output;
+    fn query(&mut self, grid: &G, pos: P) -> Self::Output;
 }
 
-pub struct Grid2D {
-    inner: Vec<Vec<char>>,
+/// How positions past the edge of a grid are treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Topology {
+    /// Positions past the edge are off-grid.
+    #[default]
+    Bounded,
+    /// Positions past an edge wrap around to the opposite edge, on both axes.
+    Toroidal,
 }
 
-impl Grid2D {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid2D<T = char> {
+    inner: Vec<Vec<T>>,
+    topology: Topology,
+}
+
+impl<T> Grid2D<T> {
+    /// Creates a new grid from rows of elements.
+    ///
+    /// All rows are expected to have the same length.
+    pub fn new(inner: Vec<Vec<T>>) -> Self {
+        Self {
+            inner,
+            topology: Topology::default(),
+        }
+    }
+
+    /// Changes how positions past the edge of the grid are treated.
+    pub fn with_topology(self, topology: Topology) -> Self {
+        Self { topology, ..self }
+    }
+
+    #[inline(always)]
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Creates a new grid of the given dimensions, populating each element with `f`.
+    pub fn from_fn<F>(dim: Dimensions2D, mut f: F) -> Self
+    where
+        F: FnMut(Pos2) -> T,
+    {
+        Self::new(
+            (0..dim.rows())
+                .map(|row| {
+                    (0..dim.cols())
+                        .map(|col| f(Pos2::from((row as isize, col as isize))))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
     /// Returns the dimensions of the grid.
     #[inline(always)]
     pub fn dim(&self) -> Dimensions2D {
         Dimensions2D {
-            rows: self.inner[0].len(),
-            cols: self.inner.len(),
+            rows: self.inner.len(),
+            cols: self.inner.first().map_or(0, Vec::len),
         }
     }
 
-    /// Returns the element at `pos`, or `None` if off-grid.
+    /// Returns a reference to the element at `pos`, or `None` if off-grid.
+    ///
+    /// On a toroidal grid, `pos` wraps around and is never off-grid.
     #[inline(always)]
-    pub fn get<P>(&self, pos: P) -> Option<char>
+    pub fn get_ref<P>(&self, pos: P) -> Option<&T>
+    where
+        P: Into<Pos2>,
+    {
+        self.resolve(pos).map(|pos| &self[pos])
+    }
+
+    /// Returns a mutable reference to the element at `pos`, or `None` if off-grid.
+    ///
+    /// On a toroidal grid, `pos` wraps around and is never off-grid.
+    #[inline(always)]
+    pub fn get_mut<P>(&mut self, pos: P) -> Option<&mut T>
+    where
+        P: Into<Pos2>,
+    {
+        self.resolve(pos).map(|pos| &mut self[pos])
+    }
+
+    /// Maps `pos` onto the grid according to the grid topology. Returns `None` if `pos` is
+    /// off-grid on a bounded grid, or if the grid is empty.
+    #[inline(always)]
+    pub fn resolve<P>(&self, pos: P) -> Option<Pos2>
     where
         P: Into<Pos2>,
     {
         let pos = pos.into();
-        if self.on_grid(pos) {
-            Some(self[pos])
-        } else {
-            None
+        match self.topology {
+            Topology::Bounded => self.on_grid(pos).then_some(pos),
+            Topology::Toroidal => {
+                let Dimensions2D { rows, cols } = self.dim();
+                if rows == 0 || cols == 0 {
+                    return None;
+                }
+                let row = WrappingI64::new(0, (0, rows as i64 - 1)) + pos.row as i64;
+                let col = WrappingI64::new(0, (0, cols as i64 - 1)) + pos.col as i64;
+                Some(Pos2::new(row.as_i64() as isize, col.as_i64() as isize))
+            }
         }
     }
 
-    /// Returns `true` if the given position is on the grid.
+    /// Returns `true` if the given position is within the bounds of the grid. This ignores the
+    /// grid topology, use `resolve` to map positions onto a toroidal grid.
     #[inline(always)]
     pub fn on_grid<P>(&self, pos: P) -> bool
     where
@@ -73,10 +179,52 @@ impl Grid2D {
         (pos.row as usize) < rows && (pos.col as usize) < cols
     }
 
+    /// Returns the positions adjacent (up, down, left, right) to `pos` that are on the grid.
+    ///
+    /// On a toroidal grid, neighbours wrap around the edges.
+    #[inline(always)]
+    pub fn neighbours(&self, pos: Pos2) -> impl Iterator<Item = Pos2> + use<'_, T> {
+        Direction::CARDINAL
+            .into_iter()
+            .filter_map(move |direction| self.resolve(pos + direction))
+    }
+
+    /// Returns an iterator over every position on the grid, in row order.
+    pub fn positions(&self) -> impl Iterator<Item = Pos2> + use<T> {
+        let Dimensions2D { rows, cols } = self.dim();
+        (0..rows)
+            .flat_map(move |row| (0..cols).map(move |col| Pos2::from((row as isize, col as isize))))
+    }
+
+    /// Queries the grid at the given position.
+    ///
+    /// This allows you to run arbitrary code starting from a given position.
+    #[inline(always)]
+    pub fn query<Q>(&self, mut query: Q, pos: Pos2) -> Q::Output
+    where
+        Q: Query<Self>,
+    {
+        query.query(self, pos)
+    }
+}
+
+impl<T> Grid2D<T>
+where
+    T: Copy,
+{
+    /// Returns the element at `pos`, or `None` if off-grid.
+    #[inline(always)]
+    pub fn get<P>(&self, pos: P) -> Option<T>
+    where
+        P: Into<Pos2>,
+    {
+        self.get_ref(pos).copied()
+    }
+
     /// Finds all elements satisfying the `Finder` implementation.
-    pub fn find_all<F>(&self, finder: F) -> Vec<(Pos2, char)>
+    pub fn find_all<F>(&self, finder: F) -> Vec<(Pos2, T)>
     where
-        F: Finder,
+        F: Finder<T>,
     {
         let mut found = Vec::default();
         for (r, row) in self.inner.iter().enumerate() {
@@ -92,9 +240,9 @@ impl Grid2D {
 
     /// Finds all elements satisfying the `Finder` implementation, returning an iterator over the
     /// results.
-    pub fn find_all_iter<F>(&self, finder: F) -> impl Iterator<Item = (Pos2, char)> + use<'_, F>
+    pub fn find_all_iter<F>(&self, finder: F) -> impl Iterator<Item = (Pos2, T)> + use<'_, F, T>
     where
-        F: Finder,
+        F: Finder<T>,
     {
         self.inner.iter().enumerate().flat_map(move |(r, row)| {
             row.iter().enumerate().filter_map({
@@ -110,21 +258,15 @@ impl Grid2D {
             })
         })
     }
+}
 
-    /// Queries the grid at the given position.
-    ///
-    /// This allows you to run arbitrary code starting from a given position.
-    #[inline(always)]
-    pub fn query<Q>(&self, mut query: Q, pos: Pos2) -> Q::Output
-    where
-        Q: Query,
-    {
-        query.query(self, pos)
-    }
-
+impl<T> Grid2D<T>
+where
+    T: Copy + Eq + Hash,
+{
     /// Returns all unique elements present in the grid.
     #[inline(always)]
-    pub fn unique(&self) -> FxHashSet<char> {
+    pub fn unique(&self) -> FxHashSet<T> {
         let mut set = FxHashSet::default();
         for row in &self.inner {
             for col in row {
@@ -135,8 +277,8 @@ impl Grid2D {
     }
 }
 
-impl Index<Pos2> for Grid2D {
-    type Output = char;
+impl<T> Index<Pos2> for Grid2D<T> {
+    type Output = T;
 
     #[inline(always)]
     fn index(&self, pos: Pos2) -> &Self::Output {
@@ -144,14 +286,21 @@ impl Index<Pos2> for Grid2D {
     }
 }
 
+impl<T> IndexMut<Pos2> for Grid2D<T> {
+    #[inline(always)]
+    fn index_mut(&mut self, pos: Pos2) -> &mut Self::Output {
+        &mut self.inner[pos.row as usize][pos.col as usize]
+    }
+}
+
 impl From<&str> for Grid2D {
     fn from(value: &str) -> Self {
-        Grid2D {
-            inner: value
+        Grid2D::new(
+            value
                 .lines()
                 .map(|row| row.chars().collect::<Vec<_>>())
                 .collect(),
-        }
+        )
     }
 }
 
@@ -163,6 +312,9 @@ mod tests {
 020
 030"#;
 
+    const GRID_2X4: &str = r#"0123
+4567"#;
+
     #[test]
     fn makes_2d_grid_from_str() {
         let grid = Grid2D::from(GRID_3X3);
@@ -171,6 +323,16 @@ mod tests {
         assert_eq!(grid.dim().cols(), 3);
     }
 
+    #[test]
+    fn non_square_grid_dimensions() {
+        let grid = Grid2D::from(GRID_2X4);
+
+        assert_eq!(grid.dim(), Dimensions2D::new(2, 4));
+        assert!(grid.on_grid((1, 3)));
+        assert!(!grid.on_grid((3, 1)));
+        assert_eq!(grid.get((1, 3)), Some('7'));
+    }
+
     #[test]
     fn determines_if_coord_is_on_map() {
         let grid = Grid2D::from(GRID_3X3);
@@ -179,6 +341,7 @@ mod tests {
         assert!(grid.on_grid((1, 1)));
         assert!(!grid.on_grid((3, 0)));
         assert!(!grid.on_grid((0, 3)));
+        assert!(!grid.on_grid((-1, 0)));
     }
 
     #[test]
@@ -188,4 +351,66 @@ mod tests {
         let expected = ['0', '1', '2', '3'].into_iter().collect::<FxHashSet<_>>();
         assert_eq!(grid.unique(), expected);
     }
+
+    #[test]
+    fn finds_neighbours_on_grid() {
+        let grid = Grid2D::from(GRID_3X3);
+
+        let mut corner = grid.neighbours(Pos2::from((0, 0))).collect::<Vec<_>>();
+        corner.sort();
+        assert_eq!(corner, vec![Pos2::from((0, 1)), Pos2::from((1, 0))]);
+
+        assert_eq!(grid.neighbours(Pos2::from((1, 1))).count(), 4);
+    }
+
+    #[test]
+    fn toroidal_grid_wraps_positions() {
+        let grid = Grid2D::from(GRID_2X4).with_topology(Topology::Toroidal);
+
+        assert_eq!(grid.get((-1, 0)), Some('4'));
+        assert_eq!(grid.get((0, -1)), Some('3'));
+        assert_eq!(grid.get((5, 9)), Some('5'));
+        assert_eq!(grid.resolve((2, 4)), Some(Pos2::new(0, 0)));
+
+        let bounded = Grid2D::from(GRID_2X4);
+        assert_eq!(bounded.get((-1, 0)), None);
+        assert_eq!(bounded.resolve((2, 4)), None);
+    }
+
+    #[test]
+    fn empty_toroidal_grid_has_no_positions() {
+        for dim in [Dimensions2D::new(0, 0), Dimensions2D::new(2, 0)] {
+            let grid = Grid2D::from_fn(dim, |_| '.').with_topology(Topology::Toroidal);
+
+            assert_eq!(grid.resolve((0, 0)), None);
+            assert_eq!(grid.get((-1, 3)), None);
+            assert_eq!(grid.neighbours(Pos2::new(0, 0)).count(), 0);
+        }
+    }
+
+    #[test]
+    fn toroidal_neighbours_wrap() {
+        let grid = Grid2D::from(GRID_3X3).with_topology(Topology::Toroidal);
+
+        let mut corner = grid.neighbours(Pos2::new(0, 0)).collect::<Vec<_>>();
+        corner.sort();
+        assert_eq!(
+            corner,
+            vec![
+                Pos2::new(0, 1),
+                Pos2::new(0, 2),
+                Pos2::new(1, 0),
+                Pos2::new(2, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn makes_grid_from_fn() {
+        let grid = Grid2D::from_fn(Dimensions2D::new(2, 3), |pos| pos.row * 10 + pos.col);
+
+        assert_eq!(grid.dim(), Dimensions2D::new(2, 3));
+        assert_eq!(grid.get((1, 2)), Some(12));
+        assert_eq!(grid.positions().count(), 6);
+    }
 }
@@ -0,0 +1,241 @@
+use smallvec::SmallVec;
+
+use super::{path, Dimensions2D, Grid2D, Pos2, Rect};
+
+/// A connected region of the grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    label: usize,
+    cells: Vec<Pos2>,
+    bounds: Rect,
+}
+
+impl Component {
+    /// Label of this component in the label grid.
+    pub fn label(&self) -> usize {
+        self.label
+    }
+
+    /// Positions of all cells in the component, in the order they were discovered.
+    pub fn cells(&self) -> &[Pos2] {
+        &self.cells
+    }
+
+    /// Smallest rectangle containing the component.
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// Total number of cells in the component.
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+/// Result of labelling the connected components of a grid.
+#[derive(Debug, Clone)]
+pub struct Components {
+    labels: Grid2D<usize>,
+    regions: Vec<Component>,
+}
+
+impl Components {
+    /// Grid where each position holds the label of the component it belongs to.
+    pub fn labels(&self) -> &Grid2D<usize> {
+        &self.labels
+    }
+
+    /// All components, indexed by their label.
+    pub fn regions(&self) -> &[Component] {
+        &self.regions
+    }
+
+    /// Returns the component containing `pos`, or `None` if off-grid.
+    pub fn at(&self, pos: Pos2) -> Option<&Component> {
+        self.labels.get(pos).map(|label| &self.regions[label])
+    }
+
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+}
+
+impl<T> Grid2D<T> {
+    /// Finds every position connected to `start` (up, down, left, right) whose element satisfies
+    /// `predicate`.
+    ///
+    /// Returns an empty `Vec` when `start` is off-grid or does not satisfy `predicate`.
+    pub fn flood_fill<F>(&self, start: Pos2, mut predicate: F) -> Vec<Pos2>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        match self.get_ref(start) {
+            Some(item) if predicate(item) => (),
+            _ => return Vec::default(),
+        }
+
+        path::flood_fill(start, |pos| {
+            self.neighbours(pos)
+                .filter(|next| predicate(&self[*next]))
+                .collect::<SmallVec<[Pos2; 4]>>()
+        })
+    }
+
+    /// Labels every connected region of the grid.
+    ///
+    /// Two adjacent positions (up, down, left, right) belong to the same region when `eq` returns
+    /// `true` for their elements.
+    pub fn components<F>(&self, mut eq: F) -> Components
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        let Dimensions2D { rows, cols } = self.dim();
+        let mut labels: Grid2D<Option<usize>> =
+            Grid2D::from_fn(Dimensions2D::new(rows, cols), |_| None);
+        let mut regions = Vec::default();
+
+        let mut stack = Vec::default();
+        for start in self.positions() {
+            if labels[start].is_some() {
+                continue;
+            }
+
+            let label = regions.len();
+            labels[start] = Some(label);
+            stack.push(start);
+
+            let mut cells = Vec::default();
+            let mut bounds = Rect::new(start, start);
+            while let Some(pos) = stack.pop() {
+                cells.push(pos);
+                bounds = bounds.expand_to(pos);
+                for next in self.neighbours(pos) {
+                    if labels[next].is_none() && eq(&self[pos], &self[next]) {
+                        labels[next] = Some(label);
+                        stack.push(next);
+                    }
+                }
+            }
+
+            regions.push(Component {
+                label,
+                cells,
+                bounds,
+            });
+        }
+
+        Components {
+            labels: Grid2D::from_fn(Dimensions2D::new(rows, cols), |pos| labels[pos].unwrap()),
+            regions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Topology;
+
+    const GARDEN: &str = r#"AAAA
+BBCD
+BBCC
+EEEC"#;
+
+    const RINGS: &str = r#"OOOOO
+OXOXO
+OOOOO
+OXOXO
+OOOOO"#;
+
+    fn sorted(mut cells: Vec<Pos2>) -> Vec<Pos2> {
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn flood_fills_matching_region() {
+        let grid = Grid2D::from(GARDEN);
+
+        let filled = grid.flood_fill(Pos2::new(1, 2), |ch| *ch == 'C');
+
+        assert_eq!(
+            sorted(filled),
+            vec![
+                Pos2::new(1, 2),
+                Pos2::new(2, 2),
+                Pos2::new(2, 3),
+                Pos2::new(3, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn flood_fill_is_empty_when_start_does_not_match() {
+        let grid = Grid2D::from(GARDEN);
+
+        assert!(grid.flood_fill(Pos2::new(0, 0), |ch| *ch == 'C').is_empty());
+        assert!(grid.flood_fill(Pos2::new(-1, 0), |_| true).is_empty());
+    }
+
+    #[test]
+    fn flood_fills_large_region_without_recursion() {
+        let grid = Grid2D::from_fn(Dimensions2D::new(500, 500), |_| 'A');
+
+        assert_eq!(
+            grid.flood_fill(Pos2::new(250, 250), |_| true).len(),
+            500 * 500
+        );
+    }
+
+    #[test]
+    fn flood_fill_wraps_around_toroidal_grid() {
+        let grid = Grid2D::from("A.A\n...\nA.A").with_topology(Topology::Toroidal);
+
+        assert_eq!(grid.flood_fill(Pos2::new(0, 0), |ch| *ch == 'A').len(), 4);
+        assert_eq!(grid.components(|a, b| a == b).len(), 2);
+    }
+
+    #[test]
+    fn labels_components() {
+        let grid = Grid2D::from(GARDEN);
+
+        let components = grid.components(|a, b| a == b);
+
+        assert_eq!(components.len(), 5);
+
+        let c = components.at(Pos2::new(3, 3)).unwrap();
+        assert_eq!(c.area(), 4);
+        assert_eq!(c.bounds(), Rect::from(((1, 2), (3, 3))));
+        assert_eq!(components.labels()[Pos2::new(1, 2)], c.label());
+
+        let b = components.at(Pos2::new(1, 0)).unwrap();
+        assert_eq!(b.area(), 4);
+        assert_eq!(b.bounds(), Rect::from(((1, 0), (2, 1))));
+
+        let total = components
+            .regions()
+            .iter()
+            .map(Component::area)
+            .sum::<usize>();
+        assert_eq!(total, 16);
+    }
+
+    #[test]
+    fn separates_regions_of_the_same_element() {
+        let grid = Grid2D::from(RINGS);
+
+        let components = grid.components(|a, b| a == b);
+
+        // the outer O region plus 4 separate X regions
+        assert_eq!(components.len(), 5);
+        assert_eq!(components.at(Pos2::new(0, 0)).unwrap().area(), 21);
+        assert_ne!(
+            components.at(Pos2::new(1, 1)).unwrap().label(),
+            components.at(Pos2::new(1, 3)).unwrap().label()
+        );
+    }
+}
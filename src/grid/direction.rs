@@ -5,6 +5,23 @@ pub struct Direction {
 }
 
 impl Direction {
+    pub const UP: Direction = Direction::new(-1, 0);
+    pub const DOWN: Direction = Direction::new(1, 0);
+    pub const LEFT: Direction = Direction::new(0, -1);
+    pub const RIGHT: Direction = Direction::new(0, 1);
+
+    /// Up, down, left, right.
+    pub const CARDINAL: [Direction; 4] = [
+        Direction::UP,
+        Direction::DOWN,
+        Direction::LEFT,
+        Direction::RIGHT,
+    ];
+
+    pub const fn new(row: isize, col: isize) -> Self {
+        Direction { row, col }
+    }
+
     pub fn row(&self) -> isize {
         self.row
     }
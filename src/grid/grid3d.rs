@@ -0,0 +1,442 @@
+use std::ops::{Index, IndexMut};
+
+use smallvec::SmallVec;
+
+use super::{path, path::Path, Finder, Pos3, Query};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dimensions3D {
+    width: usize,
+    height: usize,
+    depth: usize,
+}
+
+impl Dimensions3D {
+    pub fn new(width: usize, height: usize, depth: usize) -> Self {
+        Self {
+            width,
+            height,
+            depth,
+        }
+    }
+
+    /// Size along the `x` axis.
+    #[inline(always)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Size along the `y` axis.
+    #[inline(always)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Size along the `z` axis.
+    #[inline(always)]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Total number of cells.
+    #[inline(always)]
+    pub fn volume(&self) -> usize {
+        self.width * self.height * self.depth
+    }
+}
+
+/// Which positions count as adjacent in 3D.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Connectivity {
+    /// The 6 positions sharing a face.
+    #[default]
+    Faces,
+    /// The 26 positions sharing a face, edge or corner.
+    Surrounding,
+}
+
+impl Connectivity {
+    /// Offsets from a position to each of its neighbours.
+    pub fn offsets(&self) -> &'static [Pos3] {
+        match self {
+            Connectivity::Faces => &Pos3::FACES,
+            Connectivity::Surrounding => &Pos3::SURROUNDING,
+        }
+    }
+}
+
+/// A dense 3D grid, stored as a flat list of `z` layers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid3D<T> {
+    inner: Vec<T>,
+    dim: Dimensions3D,
+}
+
+impl<T> Grid3D<T> {
+    /// Creates a new grid of the given dimensions, populating each element with `f`.
+    pub fn from_fn<F>(dim: Dimensions3D, mut f: F) -> Self
+    where
+        F: FnMut(Pos3) -> T,
+    {
+        let mut inner = Vec::with_capacity(dim.volume());
+        for z in 0..dim.depth {
+            for y in 0..dim.height {
+                for x in 0..dim.width {
+                    inner.push(f(Pos3::new(x as isize, y as isize, z as isize)));
+                }
+            }
+        }
+        Self { inner, dim }
+    }
+
+    /// Returns the dimensions of the grid.
+    #[inline(always)]
+    pub fn dim(&self) -> Dimensions3D {
+        self.dim
+    }
+
+    #[inline(always)]
+    fn assert_on_grid(&self, pos: Pos3) {
+        let Dimensions3D {
+            width,
+            height,
+            depth,
+        } = self.dim;
+        assert!(
+            self.on_grid(pos),
+            "position {pos:?} is off the {width}x{height}x{depth} grid"
+        );
+    }
+
+    /// Index into `inner`. Only meaningful for positions on the grid.
+    #[inline(always)]
+    fn offset(&self, pos: Pos3) -> usize {
+        (pos.z as usize * self.dim.height + pos.y as usize) * self.dim.width + pos.x as usize
+    }
+
+    /// Returns a reference to the element at `pos`, or `None` if off-grid.
+    #[inline(always)]
+    pub fn get_ref<P>(&self, pos: P) -> Option<&T>
+    where
+        P: Into<Pos3>,
+    {
+        let pos = pos.into();
+        self.on_grid(pos).then(|| &self.inner[self.offset(pos)])
+    }
+
+    /// Returns a mutable reference to the element at `pos`, or `None` if off-grid.
+    #[inline(always)]
+    pub fn get_mut<P>(&mut self, pos: P) -> Option<&mut T>
+    where
+        P: Into<Pos3>,
+    {
+        let pos = pos.into();
+        if self.on_grid(pos) {
+            let offset = self.offset(pos);
+            Some(&mut self.inner[offset])
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the given position is within the bounds of the grid.
+    #[inline(always)]
+    pub fn on_grid<P>(&self, pos: P) -> bool
+    where
+        P: Into<Pos3>,
+    {
+        let pos = pos.into();
+        (pos.x as usize) < self.dim.width
+            && (pos.y as usize) < self.dim.height
+            && (pos.z as usize) < self.dim.depth
+    }
+
+    /// Returns the positions adjacent to `pos` that are on the grid.
+    #[inline(always)]
+    pub fn neighbours(
+        &self,
+        pos: Pos3,
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = Pos3> + use<'_, T> {
+        connectivity
+            .offsets()
+            .iter()
+            .map(move |offset| pos + *offset)
+            .filter(|pos| self.on_grid(*pos))
+    }
+
+    /// Returns an iterator over every position on the grid, ordered by `z`, then `y`, then `x`.
+    pub fn positions(&self) -> impl Iterator<Item = Pos3> + use<T> {
+        let Dimensions3D {
+            width,
+            height,
+            depth,
+        } = self.dim;
+        (0..depth).flat_map(move |z| {
+            (0..height).flat_map(move |y| {
+                (0..width).map(move |x| Pos3::new(x as isize, y as isize, z as isize))
+            })
+        })
+    }
+
+    /// Queries the grid at the given position.
+    ///
+    /// This allows you to run arbitrary code starting from a given position.
+    #[inline(always)]
+    pub fn query<Q>(&self, mut query: Q, pos: Pos3) -> Q::Output
+    where
+        Q: Query<Self, Pos3>,
+    {
+        query.query(self, pos)
+    }
+
+    /// Returns every position connected to `start` whose element satisfies `predicate`,
+    /// including `start` itself. Returns nothing if `start` is off-grid or doesn't match.
+    pub fn flood_fill<F>(
+        &self,
+        start: Pos3,
+        connectivity: Connectivity,
+        mut predicate: F,
+    ) -> Vec<Pos3>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        match self.get_ref(start) {
+            Some(item) if predicate(item) => (),
+            _ => return Vec::default(),
+        }
+
+        path::flood_fill(start, |pos| {
+            self.neighbours(pos, connectivity)
+                .filter(|next| predicate(&self[*next]))
+                .collect::<SmallVec<[Pos3; 26]>>()
+        })
+    }
+
+    /// Finds the shortest path from any of the `sources` to any of the `targets`.
+    ///
+    /// `passable(from, to)` decides whether a step between two adjacent positions is allowed.
+    pub fn bfs<S, G, F>(
+        &self,
+        sources: S,
+        targets: G,
+        connectivity: Connectivity,
+        passable: F,
+    ) -> Option<Path<Pos3>>
+    where
+        S: IntoIterator<Item = Pos3>,
+        G: IntoIterator<Item = Pos3>,
+        F: FnMut(Pos3, Pos3) -> bool,
+    {
+        path::bfs_between(
+            sources,
+            targets,
+            |pos| self.neighbours(pos, connectivity),
+            passable,
+        )
+    }
+}
+
+impl<T> Grid3D<T>
+where
+    T: Copy,
+{
+    /// Returns the element at `pos`, or `None` if off-grid.
+    #[inline(always)]
+    pub fn get<P>(&self, pos: P) -> Option<T>
+    where
+        P: Into<Pos3>,
+    {
+        self.get_ref(pos).copied()
+    }
+
+    /// Finds all elements satisfying the `Finder` implementation.
+    pub fn find_all<F>(&self, finder: F) -> Vec<(Pos3, T)>
+    where
+        F: Finder<T>,
+    {
+        self.positions()
+            .map(|pos| (pos, self[pos]))
+            .filter(|(_, item)| finder.check(*item))
+            .collect()
+    }
+}
+
+impl<T> Index<Pos3> for Grid3D<T> {
+    type Output = T;
+
+    #[inline(always)]
+    fn index(&self, pos: Pos3) -> &Self::Output {
+        self.assert_on_grid(pos);
+        &self.inner[self.offset(pos)]
+    }
+}
+
+impl<T> IndexMut<Pos3> for Grid3D<T> {
+    #[inline(always)]
+    fn index_mut(&mut self, pos: Pos3) -> &mut Self::Output {
+        self.assert_on_grid(pos);
+        let offset = self.offset(pos);
+        &mut self.inner[offset]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fxhash::FxHashSet;
+
+    use super::*;
+
+    /// Makes a grid from `z` layers, where each layer is a multi-line string of `y` rows.
+    fn grid(layers: &[&str]) -> Grid3D<char> {
+        let layers = layers
+            .iter()
+            .map(|layer| {
+                layer
+                    .lines()
+                    .map(|line| line.chars().collect::<Vec<_>>())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let dim = Dimensions3D::new(layers[0][0].len(), layers[0].len(), layers.len());
+        Grid3D::from_fn(dim, |pos| {
+            layers[pos.z as usize][pos.y as usize][pos.x as usize]
+        })
+    }
+
+    #[derive(Clone)]
+    struct Wall;
+
+    impl Finder for Wall {
+        fn check(&self, ch: char) -> bool {
+            ch == '#'
+        }
+    }
+
+    struct CountWalls;
+
+    impl Query<Grid3D<char>, Pos3> for CountWalls {
+        type Output = usize;
+
+        fn query(&mut self, grid: &Grid3D<char>, pos: Pos3) -> Self::Output {
+            grid.neighbours(pos, Connectivity::Surrounding)
+                .filter(|pos| grid[*pos] == '#')
+                .count()
+        }
+    }
+
+    #[test]
+    fn indexes_by_position() {
+        let grid = grid(&["ab\ncd", "ef\ngh"]);
+
+        assert_eq!(grid.dim(), Dimensions3D::new(2, 2, 2));
+        assert_eq!(grid.get((1, 0, 0)), Some('b'));
+        assert_eq!(grid.get((0, 1, 1)), Some('g'));
+        assert_eq!(grid.get((2, 0, 0)), None);
+        assert_eq!(grid.get((0, 0, -1)), None);
+        assert_eq!(
+            grid.positions().map(|pos| grid[pos]).collect::<String>(),
+            "abcdefgh"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "off the 2x2x2 grid")]
+    fn indexing_past_width_panics() {
+        let grid = grid(&["ab\ncd", "ef\ngh"]);
+
+        // would otherwise land on (0, 1, 0)
+        let _ = grid[Pos3::new(2, 0, 0)];
+    }
+
+    #[test]
+    #[should_panic(expected = "off the 2x2x2 grid")]
+    fn indexing_negative_position_panics() {
+        let mut grid = grid(&["ab\ncd", "ef\ngh"]);
+
+        grid[Pos3::new(-1, 1, 1)] = 'z';
+    }
+
+    #[test]
+    fn counts_neighbours() {
+        let grid = Grid3D::from_fn(Dimensions3D::new(3, 3, 3), |_| '.');
+
+        let center = Pos3::new(1, 1, 1);
+        assert_eq!(grid.neighbours(center, Connectivity::Faces).count(), 6);
+        assert_eq!(
+            grid.neighbours(center, Connectivity::Surrounding).count(),
+            26
+        );
+
+        let corner = Pos3::new(0, 0, 0);
+        assert_eq!(grid.neighbours(corner, Connectivity::Faces).count(), 3);
+        assert_eq!(
+            grid.neighbours(corner, Connectivity::Surrounding).count(),
+            7
+        );
+    }
+
+    #[test]
+    fn finds_and_queries() {
+        let grid = grid(&["#..\n...", "...\n..#"]);
+
+        assert_eq!(
+            grid.find_all(Wall),
+            vec![(Pos3::new(0, 0, 0), '#'), (Pos3::new(2, 1, 1), '#')]
+        );
+        assert_eq!(grid.query(CountWalls, Pos3::new(1, 0, 1)), 2);
+    }
+
+    #[test]
+    fn flood_fill_respects_connectivity() {
+        // the two walls only touch along an edge
+        let grid = grid(&["#.\n..", "..\n.#"]);
+
+        let filled = grid.flood_fill(Pos3::new(0, 0, 0), Connectivity::Faces, |ch| *ch == '#');
+        assert_eq!(filled.len(), 1);
+
+        let filled = grid.flood_fill(Pos3::new(0, 0, 0), Connectivity::Surrounding, |ch| {
+            *ch == '#'
+        });
+        assert_eq!(filled.len(), 2);
+
+        // the open cells surround the walls
+        let filled = grid.flood_fill(Pos3::new(1, 0, 0), Connectivity::Faces, |ch| *ch == '.');
+        assert_eq!(filled.len(), 6);
+    }
+
+    #[test]
+    fn bfs_moves_between_layers() {
+        let grid = grid(&["S#\n##", "..\n.E"]);
+
+        let path = grid
+            .bfs(
+                [Pos3::new(0, 0, 0)],
+                [Pos3::new(1, 1, 1)],
+                Connectivity::Faces,
+                |_, to| grid[to] != '#',
+            )
+            .unwrap();
+        assert_eq!(path.cost(), 3);
+        assert_eq!(path.end(), Pos3::new(1, 1, 1));
+
+        let path = grid
+            .bfs(
+                [Pos3::new(0, 0, 0)],
+                [Pos3::new(1, 1, 1)],
+                Connectivity::Surrounding,
+                |_, to| grid[to] != '#',
+            )
+            .unwrap();
+        assert_eq!(path.cost(), 1);
+    }
+
+    #[test]
+    fn surrounding_offsets_are_unique() {
+        let offsets = Pos3::SURROUNDING.iter().collect::<FxHashSet<_>>();
+
+        assert_eq!(offsets.len(), 26);
+        assert!(!offsets.contains(&Pos3::new(0, 0, 0)));
+        assert!(Pos3::FACES.iter().all(|offset| offsets.contains(offset)));
+    }
+}
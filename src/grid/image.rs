@@ -0,0 +1,319 @@
+//! Exporting grids as images, and recording simulation frames as image sequences or animated GIFs.
+//!
+//! Each grid element is drawn as a `scale` × `scale` square, using a palette closure that maps
+//! elements to colours.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use color_eyre::eyre::{bail, ensure, Result};
+use fxhash::FxHashMap;
+
+use super::{render::Colour, Dimensions2D, Grid2D, Pos2};
+
+/// An RGB image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    /// Creates an image from a grid, drawing each element as a `scale` × `scale` square coloured
+    /// by `palette`.
+    pub fn from_grid<T, F>(grid: &Grid2D<T>, scale: usize, mut palette: F) -> Self
+    where
+        F: FnMut(&T) -> Colour,
+    {
+        let Dimensions2D { rows, cols } = grid.dim();
+        let (width, height) = (cols * scale, rows * scale);
+
+        let mut pixels = vec![[0; 3]; width * height];
+        for pos in grid.positions() {
+            let rgb = palette(&grid[pos]).rgb();
+            let (x, y) = (pos.col as usize * scale, pos.row as usize * scale);
+            for row in y..y + scale {
+                pixels[row * width + x..row * width + x + scale].fill(rgb);
+            }
+        }
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the colour of the pixel at `pos`, where `row` is the `y` coordinate.
+    pub fn pixel(&self, pos: Pos2) -> [u8; 3] {
+        self.pixels[pos.row as usize * self.width + pos.col as usize]
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        self.pixels.iter().flatten().copied().collect()
+    }
+
+    /// Writes the image as a binary PPM (`P6`).
+    pub fn write_ppm<W>(&self, mut out: W) -> Result<()>
+    where
+        W: Write,
+    {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        out.write_all(&self.bytes())?;
+        Ok(())
+    }
+
+    /// Writes the image as a PNG.
+    pub fn write_png<W>(&self, out: W) -> Result<()>
+    where
+        W: Write,
+    {
+        let mut encoder = png::Encoder::new(out, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.bytes())?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Saves the image, picking the format from the extension of `path` (`png` or `ppm`).
+    pub fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let out = BufWriter::new(File::create(path)?);
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("png") => self.write_png(out),
+            Some("ppm") => self.write_ppm(out),
+            _ => bail!("unsupported image format: {}", path.display()),
+        }
+    }
+}
+
+/// Collects frames of a step-driven simulation.
+///
+/// Record a frame after each step, then write the frames out as an animated GIF or as a numbered
+/// sequence of images.
+pub struct FrameRecorder<F> {
+    scale: usize,
+    palette: F,
+    // delay between frames, in hundredths of a second
+    delay: u16,
+    frames: Vec<Image>,
+}
+
+impl<F> FrameRecorder<F> {
+    /// Creates a recorder drawing each element as a `scale` × `scale` square coloured by
+    /// `palette`.
+    pub fn new(scale: usize, palette: F) -> Self {
+        Self {
+            scale,
+            palette,
+            delay: 10,
+            frames: Vec::default(),
+        }
+    }
+
+    /// Sets the delay between frames of the GIF, in hundredths of a second.
+    pub fn with_delay(self, delay: u16) -> Self {
+        Self { delay, ..self }
+    }
+
+    /// Draws `grid` as the next frame.
+    pub fn record<T>(&mut self, grid: &Grid2D<T>)
+    where
+        F: FnMut(&T) -> Colour,
+    {
+        let frame = Image::from_grid(grid, self.scale, &mut self.palette);
+        self.frames.push(frame);
+    }
+
+    pub fn frames(&self) -> &[Image] {
+        &self.frames
+    }
+
+    /// Writes every frame as `<prefix>_<n>.<extension>` into `dir`, where `extension` is `png` or
+    /// `ppm`.
+    pub fn save_sequence<P>(&self, dir: P, prefix: &str, extension: &str) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        for (n, frame) in self.frames.iter().enumerate() {
+            frame.save(dir.as_ref().join(format!("{prefix}_{n:05}.{extension}")))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the frames as a looping animated GIF.
+    ///
+    /// GIFs are limited to 256 colours and 65535 pixels on each side.
+    pub fn write_gif<W>(&self, out: W) -> Result<()>
+    where
+        W: Write,
+    {
+        let Some(first) = self.frames.first() else {
+            bail!("no frames recorded");
+        };
+        ensure!(
+            self.frames
+                .iter()
+                .all(|frame| frame.width == first.width && frame.height == first.height),
+            "all frames must be the same size"
+        );
+        let (Ok(width), Ok(height)) = (u16::try_from(first.width), u16::try_from(first.height))
+        else {
+            bail!("frames are too large for a GIF");
+        };
+
+        // one palette shared by every frame
+        let mut indices: FxHashMap<[u8; 3], u8> = FxHashMap::default();
+        let mut palette = Vec::default();
+        let mut buffers = Vec::with_capacity(self.frames.len());
+        for frame in &self.frames {
+            let mut buffer = Vec::with_capacity(frame.pixels.len());
+            for rgb in &frame.pixels {
+                let index = match indices.get(rgb) {
+                    Some(index) => *index,
+                    None => {
+                        ensure!(indices.len() < 256, "GIFs are limited to 256 colours");
+                        let index = indices.len() as u8;
+                        indices.insert(*rgb, index);
+                        palette.extend_from_slice(rgb);
+                        index
+                    }
+                };
+                buffer.push(index);
+            }
+            buffers.push(buffer);
+        }
+
+        let mut encoder = gif::Encoder::new(out, width, height, &palette)?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        for buffer in buffers {
+            encoder.write_frame(&gif::Frame {
+                width,
+                height,
+                delay: self.delay,
+                buffer: buffer.into(),
+                ..gif::Frame::default()
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Saves the frames as a looping animated GIF.
+    pub fn save_gif<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        self.write_gif(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<T> Grid2D<T> {
+    /// Draws the grid as an image. See `Image::from_grid`.
+    pub fn to_image<F>(&self, scale: usize, palette: F) -> Image
+    where
+        F: FnMut(&T) -> Colour,
+    {
+        Image::from_grid(self, scale, palette)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette(ch: &char) -> Colour {
+        match ch {
+            '#' => Colour::White,
+            _ => Colour::Black,
+        }
+    }
+
+    #[test]
+    fn scales_cells() {
+        let grid = Grid2D::from("#.\n.#\n..");
+
+        let image = grid.to_image(2, palette);
+        assert_eq!((image.width(), image.height()), (4, 6));
+        assert_eq!(image.pixel(Pos2::new(1, 1)), Colour::White.rgb());
+        assert_eq!(image.pixel(Pos2::new(1, 2)), Colour::Black.rgb());
+        assert_eq!(image.pixel(Pos2::new(3, 3)), Colour::White.rgb());
+        assert_eq!(image.pixel(Pos2::new(5, 3)), Colour::Black.rgb());
+    }
+
+    #[test]
+    fn writes_ppm() {
+        let grid = Grid2D::from("#.");
+
+        let mut out = Vec::default();
+        grid.to_image(1, palette).write_ppm(&mut out).unwrap();
+
+        let mut expected = b"P6\n2 1\n255\n".to_vec();
+        expected.extend([229, 229, 229, 0, 0, 0]);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn writes_png() {
+        let grid = Grid2D::from("#.\n.#");
+
+        let mut out = Vec::default();
+        grid.to_image(3, palette).write_png(&mut out).unwrap();
+
+        assert_eq!(&out[..8], b"\x89PNG\r\n\x1a\n");
+        let decoder = png::Decoder::new(std::io::Cursor::new(out));
+        let info = decoder.read_info().unwrap().info().clone();
+        assert_eq!((info.width, info.height), (6, 6));
+    }
+
+    #[test]
+    fn records_animated_gif() {
+        let mut recorder = FrameRecorder::new(1, palette).with_delay(5);
+        let mut grid = Grid2D::from("#..");
+        for _ in 0..3 {
+            recorder.record(&grid);
+            grid = grid.flip_h();
+        }
+        assert_eq!(recorder.frames().len(), 3);
+
+        let mut out = Vec::default();
+        recorder.write_gif(&mut out).unwrap();
+
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(std::io::Cursor::new(out))
+            .unwrap();
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!((frame.width, frame.height), (3, 1));
+            assert_eq!(frame.delay, 5);
+            frames += 1;
+        }
+        assert_eq!(frames, 3);
+    }
+
+    #[test]
+    fn gif_needs_frames() {
+        let recorder = FrameRecorder::new(1, palette);
+
+        assert!(recorder.write_gif(Vec::default()).is_err());
+    }
+}
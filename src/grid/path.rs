@@ -0,0 +1,692 @@
+//! Shortest path searches.
+//!
+//! The search functions work on any node type so they can be reused outside of a `Grid2D`. The
+//! `Grid2D` methods at the bottom of this module wire them up to grid positions.
+
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::Entry, BinaryHeap, VecDeque},
+    hash::Hash,
+};
+
+use fxhash::{FxHashMap, FxHashSet};
+use smallvec::SmallVec;
+
+use super::{Grid2D, Pos2};
+
+/// A path found by one of the searches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N = Pos2> {
+    cost: u64,
+    nodes: Vec<N>,
+}
+
+impl<N> Path<N> {
+    /// Total cost of the path. For BFS this is the number of steps.
+    pub fn cost(&self) -> u64 {
+        self.cost
+    }
+
+    /// All nodes on the path, starting at the source and ending at the target.
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    pub fn into_nodes(self) -> Vec<N> {
+        self.nodes
+    }
+}
+
+impl<N> Path<N>
+where
+    N: Copy,
+{
+    /// The source the path started from.
+    pub fn start(&self) -> N {
+        self.nodes[0]
+    }
+
+    /// The target the path reached.
+    pub fn end(&self) -> N {
+        self.nodes[self.nodes.len() - 1]
+    }
+}
+
+/// Every shortest path from the sources to the cheapest reachable targets.
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<N = Pos2> {
+    cost: u64,
+    targets: Vec<N>,
+    predecessors: FxHashMap<N, SmallVec<[N; 4]>>,
+}
+
+impl<N> ShortestPaths<N>
+where
+    N: Copy + Eq + Hash,
+{
+    /// Cost shared by all of the shortest paths.
+    pub fn cost(&self) -> u64 {
+        self.cost
+    }
+
+    /// Targets reached at the shortest cost.
+    pub fn targets(&self) -> &[N] {
+        &self.targets
+    }
+
+    /// Returns every node that lies on at least one shortest path.
+    pub fn nodes(&self) -> FxHashSet<N> {
+        let mut seen = FxHashSet::default();
+        let mut stack = self.targets.clone();
+        while let Some(node) = stack.pop() {
+            if seen.insert(node) {
+                if let Some(previous) = self.predecessors.get(&node) {
+                    stack.extend(previous.iter().copied());
+                }
+            }
+        }
+        seen
+    }
+
+    /// Enumerates every shortest path.
+    ///
+    /// The number of paths can grow exponentially with the size of the search space, so prefer
+    /// `nodes` when only the visited nodes matter.
+    pub fn paths(&self) -> Vec<Path<N>> {
+        let mut paths = Vec::default();
+        for target in &self.targets {
+            let mut partial = vec![*target];
+            self.paths_impl(&mut partial, &mut paths);
+        }
+        paths
+    }
+
+    fn paths_impl(&self, partial: &mut Vec<N>, paths: &mut Vec<Path<N>>) {
+        let current = partial[partial.len() - 1];
+        match self.predecessors.get(&current) {
+            Some(previous) if !previous.is_empty() => {
+                for node in previous {
+                    partial.push(*node);
+                    self.paths_impl(partial, paths);
+                    partial.pop();
+                }
+            }
+            _ => paths.push(Path {
+                cost: self.cost,
+                nodes: partial.iter().rev().copied().collect(),
+            }),
+        }
+    }
+}
+
+/// Breadth-first search where every step costs 1.
+///
+/// Returns the first path found from any of the `sources` to a node satisfying `is_target`.
+pub fn bfs<N, S, T, F, I>(sources: S, mut is_target: T, mut successors: F) -> Option<Path<N>>
+where
+    N: Copy + Eq + Hash,
+    S: IntoIterator<Item = N>,
+    T: FnMut(N) -> bool,
+    F: FnMut(N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    let mut parents: FxHashMap<N, Option<N>> = FxHashMap::default();
+    let mut queue = VecDeque::default();
+
+    for source in sources {
+        if parents.insert(source, None).is_none() {
+            queue.push_back((source, 0));
+        }
+    }
+
+    while let Some((node, cost)) = queue.pop_front() {
+        if is_target(node) {
+            return Some(Path {
+                cost,
+                nodes: reconstruct(&parents, node),
+            });
+        }
+        for next in successors(node) {
+            if let Entry::Vacant(entry) = parents.entry(next) {
+                entry.insert(Some(node));
+                queue.push_back((next, cost + 1));
+            }
+        }
+    }
+
+    None
+}
+
+/// Breadth-first search from any of the `sources` to any of the `targets`, stepping from a node
+/// to each of its `neighbours` that `passable(from, to)` allows.
+///
+/// Shared by the grid `bfs` methods.
+pub(super) fn bfs_between<N, S, G, F, I, P>(
+    sources: S,
+    targets: G,
+    mut neighbours: F,
+    mut passable: P,
+) -> Option<Path<N>>
+where
+    N: Copy + Eq + Hash,
+    S: IntoIterator<Item = N>,
+    G: IntoIterator<Item = N>,
+    F: FnMut(N) -> I,
+    I: IntoIterator<Item = N>,
+    P: FnMut(N, N) -> bool,
+{
+    let targets = targets.into_iter().collect::<FxHashSet<_>>();
+    bfs(
+        sources,
+        |node| targets.contains(&node),
+        |node| {
+            neighbours(node)
+                .into_iter()
+                .filter(|next| passable(node, *next))
+                .collect::<SmallVec<[N; 8]>>()
+        },
+    )
+}
+
+/// Depth-first flood returning every node reachable from `start`, including `start`, in the
+/// order they were discovered. Doesn't recurse, so large areas can't overflow the stack.
+pub fn flood_fill<N, F, I>(start: N, mut successors: F) -> Vec<N>
+where
+    N: Copy + Eq + Hash,
+    F: FnMut(N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    let mut visited = FxHashSet::default();
+    visited.insert(start);
+
+    let mut filled = Vec::default();
+    let mut stack = vec![start];
+    while let Some(node) = stack.pop() {
+        filled.push(node);
+        for next in successors(node) {
+            if visited.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+
+    filled
+}
+
+/// Breadth-first flood returning the number of steps to every node reachable from `sources`.
+pub fn bfs_distances<N, S, F, I>(sources: S, mut successors: F) -> FxHashMap<N, u64>
+where
+    N: Copy + Eq + Hash,
+    S: IntoIterator<Item = N>,
+    F: FnMut(N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    let mut distances = FxHashMap::default();
+    let mut queue = VecDeque::default();
+
+    for source in sources {
+        if distances.insert(source, 0).is_none() {
+            queue.push_back(source);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        let cost = distances[&node];
+        for next in successors(node) {
+            if let Entry::Vacant(entry) = distances.entry(next) {
+                entry.insert(cost + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    distances
+}
+
+/// Dijkstra's algorithm. `successors` returns each neighbouring node along with the cost to step
+/// onto it.
+pub fn dijkstra<N, S, T, F, I>(sources: S, is_target: T, successors: F) -> Option<Path<N>>
+where
+    N: Copy + Eq + Hash,
+    S: IntoIterator<Item = N>,
+    T: FnMut(N) -> bool,
+    F: FnMut(N) -> I,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    astar(sources, is_target, successors, |_| 0)
+}
+
+/// A* search.
+///
+/// `heuristic` must never overestimate the remaining cost to the nearest target, otherwise the
+/// returned path may not be the shortest one.
+pub fn astar<N, S, T, F, I, H>(
+    sources: S,
+    mut is_target: T,
+    mut successors: F,
+    mut heuristic: H,
+) -> Option<Path<N>>
+where
+    N: Copy + Eq + Hash,
+    S: IntoIterator<Item = N>,
+    T: FnMut(N) -> bool,
+    F: FnMut(N) -> I,
+    I: IntoIterator<Item = (N, u64)>,
+    H: FnMut(N) -> u64,
+{
+    let mut best: FxHashMap<N, (u64, Option<N>)> = FxHashMap::default();
+    let mut queue = BinaryHeap::default();
+
+    for source in sources {
+        if best.insert(source, (0, None)).is_none() {
+            queue.push(Frontier::new(source, 0, heuristic(source)));
+        }
+    }
+
+    while let Some(Frontier { node, cost, .. }) = queue.pop() {
+        if cost > best[&node].0 {
+            // stale entry, a cheaper route was already found
+            continue;
+        }
+        if is_target(node) {
+            let parents = best
+                .iter()
+                .map(|(node, (_, parent))| (*node, *parent))
+                .collect();
+            return Some(Path {
+                cost,
+                nodes: reconstruct(&parents, node),
+            });
+        }
+        for (next, step) in successors(node) {
+            let next_cost = cost + step;
+            let improved = best.get(&next).is_none_or(|(known, _)| next_cost < *known);
+            if improved {
+                best.insert(next, (next_cost, Some(node)));
+                queue.push(Frontier::new(next, next_cost, next_cost + heuristic(next)));
+            }
+        }
+    }
+
+    None
+}
+
+/// Dijkstra's algorithm that keeps track of every path tied for the shortest cost.
+pub fn dijkstra_all<N, S, T, F, I>(
+    sources: S,
+    mut is_target: T,
+    mut successors: F,
+) -> Option<ShortestPaths<N>>
+where
+    N: Copy + Eq + Hash,
+    S: IntoIterator<Item = N>,
+    T: FnMut(N) -> bool,
+    F: FnMut(N) -> I,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    let mut best: FxHashMap<N, u64> = FxHashMap::default();
+    let mut predecessors: FxHashMap<N, SmallVec<[N; 4]>> = FxHashMap::default();
+    let mut queue = BinaryHeap::default();
+
+    for source in sources {
+        if best.insert(source, 0).is_none() {
+            predecessors.insert(source, SmallVec::default());
+            queue.push(Frontier::new(source, 0, 0));
+        }
+    }
+
+    let mut found: Option<(u64, Vec<N>)> = None;
+
+    while let Some(Frontier { node, cost, .. }) = queue.pop() {
+        if cost > best[&node] {
+            continue;
+        }
+        if let Some((target_cost, targets)) = &mut found {
+            // everything left in the queue is more expensive than the shortest path
+            if cost > *target_cost {
+                break;
+            }
+            if is_target(node) {
+                targets.push(node);
+            }
+        } else if is_target(node) {
+            found = Some((cost, vec![node]));
+        }
+        for (next, step) in successors(node) {
+            let next_cost = cost + step;
+            match best.get(&next).map(|known| next_cost.cmp(known)) {
+                Some(Ordering::Greater) => (),
+                Some(Ordering::Equal) => {
+                    predecessors.entry(next).or_default().push(node);
+                }
+                Some(Ordering::Less) | None => {
+                    best.insert(next, next_cost);
+                    predecessors.insert(next, SmallVec::from_elem(node, 1));
+                    queue.push(Frontier::new(next, next_cost, next_cost));
+                }
+            }
+        }
+    }
+
+    found.map(|(cost, targets)| ShortestPaths {
+        cost,
+        targets,
+        predecessors,
+    })
+}
+
+/// Walks the parent links back to a source.
+fn reconstruct<N>(parents: &FxHashMap<N, Option<N>>, end: N) -> Vec<N>
+where
+    N: Copy + Eq + Hash,
+{
+    let mut nodes = vec![end];
+    let mut current = end;
+    while let Some(Some(parent)) = parents.get(&current) {
+        nodes.push(*parent);
+        current = *parent;
+    }
+    nodes.reverse();
+    nodes
+}
+
+/// Entry in the priority queue. Ordered so the `BinaryHeap` pops the lowest priority first.
+struct Frontier<N> {
+    node: N,
+    cost: u64,
+    priority: u64,
+}
+
+impl<N> Frontier<N> {
+    fn new(node: N, cost: u64, priority: u64) -> Self {
+        Self {
+            node,
+            cost,
+            priority,
+        }
+    }
+}
+
+impl<N> PartialEq for Frontier<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.cost == other.cost
+    }
+}
+
+impl<N> Eq for Frontier<N> {}
+
+impl<N> PartialOrd for Frontier<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> Ord for Frontier<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // prefer deeper nodes on ties so A* heads towards the target
+        other
+            .priority
+            .cmp(&self.priority)
+            .then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+
+impl<T> Grid2D<T> {
+    /// Finds the shortest path from any of the `sources` to any of the `targets`, moving up, down,
+    /// left and right.
+    ///
+    /// `passable(from, to)` decides whether a step between two adjacent positions is allowed.
+    pub fn bfs<S, G, F>(&self, sources: S, targets: G, passable: F) -> Option<Path>
+    where
+        S: IntoIterator<Item = Pos2>,
+        G: IntoIterator<Item = Pos2>,
+        F: FnMut(Pos2, Pos2) -> bool,
+    {
+        bfs_between(sources, targets, |pos| self.neighbours(pos), passable)
+    }
+
+    /// Finds the cheapest path from any of the `sources` to any of the `targets`, moving up, down,
+    /// left and right.
+    ///
+    /// `cost(from, to)` returns the cost of stepping between two adjacent positions, or `None` if
+    /// the step is not allowed.
+    pub fn dijkstra<S, G, F>(&self, sources: S, targets: G, mut cost: F) -> Option<Path>
+    where
+        S: IntoIterator<Item = Pos2>,
+        G: IntoIterator<Item = Pos2>,
+        F: FnMut(Pos2, Pos2) -> Option<u64>,
+    {
+        let targets = targets.into_iter().collect::<FxHashSet<_>>();
+        dijkstra(
+            sources,
+            |pos| targets.contains(&pos),
+            |pos| self.weighted_neighbours(pos, &mut cost),
+        )
+    }
+
+    /// Same as `dijkstra`, but guided by the manhattan distance to the nearest target.
+    ///
+    /// Every step must cost at least 1 for the result to be the cheapest path.
+    pub fn astar<S, G, F>(&self, sources: S, targets: G, mut cost: F) -> Option<Path>
+    where
+        S: IntoIterator<Item = Pos2>,
+        G: IntoIterator<Item = Pos2>,
+        F: FnMut(Pos2, Pos2) -> Option<u64>,
+    {
+        let targets = targets.into_iter().collect::<Vec<_>>();
+        let lookup = targets.iter().copied().collect::<FxHashSet<_>>();
+        astar(
+            sources,
+            |pos| lookup.contains(&pos),
+            |pos| self.weighted_neighbours(pos, &mut cost),
+            |pos| {
+                targets
+                    .iter()
+                    .map(|target| pos.manhattan(*target))
+                    .min()
+                    .unwrap_or(0)
+            },
+        )
+    }
+
+    /// Finds every path tied for the cheapest cost from any of the `sources` to any of the
+    /// `targets`.
+    pub fn all_shortest_paths<S, G, F>(
+        &self,
+        sources: S,
+        targets: G,
+        mut cost: F,
+    ) -> Option<ShortestPaths>
+    where
+        S: IntoIterator<Item = Pos2>,
+        G: IntoIterator<Item = Pos2>,
+        F: FnMut(Pos2, Pos2) -> Option<u64>,
+    {
+        let targets = targets.into_iter().collect::<FxHashSet<_>>();
+        dijkstra_all(
+            sources,
+            |pos| targets.contains(&pos),
+            |pos| self.weighted_neighbours(pos, &mut cost),
+        )
+    }
+
+    #[inline(always)]
+    fn weighted_neighbours<F>(&self, pos: Pos2, cost: &mut F) -> SmallVec<[(Pos2, u64); 4]>
+    where
+        F: FnMut(Pos2, Pos2) -> Option<u64>,
+    {
+        self.neighbours(pos)
+            .filter_map(|next| cost(pos, next).map(|cost| (next, cost)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Topology;
+
+    const MAZE: &str = r#"S.#.....
+.##.###.
+....#...
+.##...#E"#;
+
+    const WEIGHTED: &str = r#"1163
+1381
+2136"#;
+
+    fn find(grid: &Grid2D, ch: char) -> Pos2 {
+        grid.positions().find(|pos| grid[*pos] == ch).unwrap()
+    }
+
+    fn open(grid: &Grid2D) -> impl FnMut(Pos2, Pos2) -> bool + '_ {
+        |_, to| grid[to] != '#'
+    }
+
+    fn digit_cost(grid: &Grid2D) -> impl FnMut(Pos2, Pos2) -> Option<u64> + '_ {
+        |_, to| grid[to].to_digit(10).map(u64::from)
+    }
+
+    #[test]
+    fn bfs_finds_shortest_path_through_maze() {
+        let grid = Grid2D::from(MAZE);
+        let (start, end) = (find(&grid, 'S'), find(&grid, 'E'));
+
+        let path = grid.bfs([start], [end], open(&grid)).unwrap();
+
+        assert_eq!(path.cost(), 12);
+        assert_eq!(path.nodes().len(), 13);
+        assert_eq!(path.start(), start);
+        assert_eq!(path.end(), end);
+
+        // every step is adjacent and passable
+        for pair in path.nodes().windows(2) {
+            assert_eq!(pair[0].manhattan(pair[1]), 1);
+            assert_ne!(grid[pair[1]], '#');
+        }
+    }
+
+    #[test]
+    fn bfs_uses_closest_of_multiple_sources_and_targets() {
+        let grid = Grid2D::from(MAZE);
+        let sources = [Pos2::new(0, 0), Pos2::new(0, 7)];
+        let targets = [Pos2::new(3, 7), Pos2::new(3, 0)];
+
+        let path = grid.bfs(sources, targets, open(&grid)).unwrap();
+
+        assert_eq!(path.cost(), 3);
+        assert!(sources.contains(&path.start()));
+        assert!(targets.contains(&path.end()));
+    }
+
+    #[test]
+    fn bfs_returns_none_when_unreachable() {
+        let grid = Grid2D::from(MAZE);
+        let path = grid.bfs([Pos2::new(0, 0)], [Pos2::new(0, 2)], open(&grid));
+
+        assert!(path.is_none());
+    }
+
+    #[test]
+    fn bfs_distances_floods_reachable_nodes() {
+        let grid = Grid2D::from(MAZE);
+        let distances = bfs_distances([Pos2::new(0, 0)], |pos| {
+            grid.neighbours(pos)
+                .filter(|next| grid[*next] != '#')
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(distances[&Pos2::new(0, 0)], 0);
+        assert_eq!(distances[&Pos2::new(2, 3)], 5);
+        assert_eq!(distances[&find(&grid, 'E')], 12);
+    }
+
+    #[test]
+    fn flood_fill_works_on_any_node_type() {
+        // numbers below 20 reachable from 1 by doubling or adding 3
+        let filled = flood_fill(1u32, |n| {
+            [n * 2, n + 3].into_iter().filter(|next| *next < 20)
+        });
+
+        assert_eq!(filled[0], 1);
+        // neither step can reach a multiple of 3
+        assert_eq!(filled.len(), 13);
+        assert!(filled.iter().all(|n| n % 3 != 0));
+    }
+
+    #[test]
+    fn dijkstra_finds_cheapest_path() {
+        let grid = Grid2D::from(WEIGHTED);
+
+        let path = grid
+            .dijkstra([Pos2::new(0, 0)], [Pos2::new(2, 3)], digit_cost(&grid))
+            .unwrap();
+
+        // down, down, right, right, right
+        assert_eq!(path.cost(), 1 + 2 + 1 + 3 + 6);
+        assert_eq!(path.start(), Pos2::new(0, 0));
+        assert_eq!(path.end(), Pos2::new(2, 3));
+    }
+
+    #[test]
+    fn astar_matches_dijkstra() {
+        let grid = Grid2D::from(WEIGHTED);
+        let start = [Pos2::new(0, 0)];
+
+        for target in grid.positions() {
+            let dijkstra = grid.dijkstra(start, [target], digit_cost(&grid)).unwrap();
+            let astar = grid.astar(start, [target], digit_cost(&grid)).unwrap();
+            assert_eq!(dijkstra.cost(), astar.cost(), "failed on {target:?}");
+        }
+    }
+
+    #[test]
+    fn bfs_wraps_around_toroidal_grid() {
+        let grid = Grid2D::from(MAZE).with_topology(Topology::Toroidal);
+
+        let path = grid
+            .bfs([Pos2::new(0, 0)], [find(&grid, 'E')], open(&grid))
+            .unwrap();
+
+        // up from S wraps to the bottom row, then left wraps to E
+        assert_eq!(path.cost(), 2);
+        assert_eq!(
+            path.nodes(),
+            &[Pos2::new(0, 0), Pos2::new(3, 0), Pos2::new(3, 7)]
+        );
+    }
+
+    #[test]
+    fn finds_all_shortest_paths() {
+        let grid = Grid2D::from("...\n...\n...");
+
+        let paths = grid
+            .all_shortest_paths([Pos2::new(0, 0)], [Pos2::new(2, 2)], |_, _| Some(1))
+            .unwrap();
+
+        assert_eq!(paths.cost(), 4);
+        assert_eq!(paths.paths().len(), 6);
+        assert_eq!(paths.nodes().len(), 9);
+        for path in paths.paths() {
+            assert_eq!(path.nodes().len(), 5);
+            assert_eq!(path.start(), Pos2::new(0, 0));
+            assert_eq!(path.end(), Pos2::new(2, 2));
+        }
+    }
+
+    #[test]
+    fn all_shortest_paths_only_includes_cheapest_targets() {
+        let grid = Grid2D::from(MAZE);
+        let targets = [Pos2::new(0, 3), find(&grid, 'E')];
+
+        let paths = grid
+            .all_shortest_paths([Pos2::new(0, 0)], targets, |_, to| {
+                (grid[to] != '#').then_some(1)
+            })
+            .unwrap();
+
+        assert_eq!(paths.cost(), 7);
+        assert_eq!(paths.targets(), &[Pos2::new(0, 3)]);
+        assert!(!paths.nodes().contains(&find(&grid, 'E')));
+    }
+}
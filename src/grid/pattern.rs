@@ -0,0 +1,344 @@
+//! Matching small 2D patterns against a grid.
+//!
+//! Patterns can be parsed from a multi-line string such as `"M.S\n.A.\nM.S"`, where `.` matches
+//! any element, and expanded into all of their rotations and reflections.
+
+use super::{Dimensions2D, Grid2D, Pos2};
+
+/// One of the 8 ways to rotate and reflect a pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Orientation {
+    rotation: u8,
+    flipped: bool,
+}
+
+impl Orientation {
+    pub const IDENTITY: Orientation = Orientation::new(0, false);
+
+    /// All rotations, followed by all rotations of the horizontally flipped pattern.
+    pub const ALL: [Orientation; 8] = [
+        Orientation::new(0, false),
+        Orientation::new(1, false),
+        Orientation::new(2, false),
+        Orientation::new(3, false),
+        Orientation::new(0, true),
+        Orientation::new(1, true),
+        Orientation::new(2, true),
+        Orientation::new(3, true),
+    ];
+
+    /// `rotation` is the number of clockwise quarter turns. When `flipped` is set, the pattern is
+    /// flipped horizontally before rotating.
+    pub const fn new(rotation: u8, flipped: bool) -> Self {
+        Self {
+            rotation: rotation % 4,
+            flipped,
+        }
+    }
+
+    /// Number of clockwise quarter turns.
+    pub fn rotation(&self) -> u8 {
+        self.rotation
+    }
+
+    /// Whether the pattern was flipped horizontally before rotating.
+    pub fn flipped(&self) -> bool {
+        self.flipped
+    }
+}
+
+/// A rectangular pattern where each cell either has to match exactly or is a wildcard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern<T = char> {
+    cells: Vec<Vec<Option<T>>>,
+}
+
+impl Pattern {
+    /// Character used for wildcards when converting from a `&str`.
+    pub const WILDCARD: char = '.';
+
+    /// Parses a multi-line pattern where `wildcard` matches any element.
+    pub fn with_wildcard(pattern: &str, wildcard: char) -> Self {
+        Self::new(
+            pattern
+                .lines()
+                .map(|line| {
+                    line.chars()
+                        .map(|ch| (ch != wildcard).then_some(ch))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+}
+
+impl<T> Pattern<T> {
+    /// Creates a new pattern from rows of cells, where `None` is a wildcard.
+    ///
+    /// All rows are expected to have the same length.
+    pub fn new(cells: Vec<Vec<Option<T>>>) -> Self {
+        Self { cells }
+    }
+
+    pub fn dim(&self) -> Dimensions2D {
+        Dimensions2D::new(
+            self.cells.len(),
+            self.cells.first().map(Vec::len).unwrap_or(0),
+        )
+    }
+
+    /// Returns the cell at `pos` where `None` is a wildcard.
+    pub fn get(&self, pos: Pos2) -> Option<&T> {
+        self.cells[pos.row as usize][pos.col as usize].as_ref()
+    }
+}
+
+impl<T> Pattern<T>
+where
+    T: Clone + PartialEq,
+{
+    /// Returns a copy of this pattern rotated and reflected according to `orientation`.
+    pub fn oriented(&self, orientation: Orientation) -> Self {
+        let mut pattern = self.clone();
+        if orientation.flipped() {
+            pattern = pattern.flipped();
+        }
+        for _ in 0..orientation.rotation() {
+            pattern = pattern.rotated();
+        }
+        pattern
+    }
+
+    /// All distinct rotations of this pattern.
+    pub fn rotations(self) -> PatternSet<T> {
+        PatternSet::with_orientations(self, &Orientation::ALL[..4])
+    }
+
+    /// All distinct rotations and reflections of this pattern.
+    pub fn symmetries(self) -> PatternSet<T> {
+        PatternSet::with_orientations(self, &Orientation::ALL)
+    }
+
+    /// Rotates a quarter turn clockwise.
+    fn rotated(&self) -> Self {
+        let Dimensions2D { rows, cols } = self.dim();
+        Self {
+            cells: (0..cols)
+                .map(|row| {
+                    (0..rows)
+                        .map(|col| self.cells[rows - 1 - col][row].clone())
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// Flips horizontally.
+    fn flipped(&self) -> Self {
+        Self {
+            cells: self
+                .cells
+                .iter()
+                .map(|row| row.iter().rev().cloned().collect())
+                .collect(),
+        }
+    }
+}
+
+impl From<&str> for Pattern {
+    fn from(pattern: &str) -> Self {
+        Self::with_wildcard(pattern, Pattern::WILDCARD)
+    }
+}
+
+/// A group of distinct patterns, each tagged with the orientation it was made from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternSet<T = char> {
+    patterns: Vec<(Orientation, Pattern<T>)>,
+}
+
+impl<T> PatternSet<T>
+where
+    T: Clone + PartialEq,
+{
+    /// Creates the pattern in each of the `orientations`, skipping any that look identical to an
+    /// earlier one.
+    pub fn with_orientations(pattern: Pattern<T>, orientations: &[Orientation]) -> Self {
+        let mut patterns: Vec<(Orientation, Pattern<T>)> = Vec::default();
+        for orientation in orientations {
+            let oriented = pattern.oriented(*orientation);
+            if !patterns.iter().any(|(_, existing)| *existing == oriented) {
+                patterns.push((*orientation, oriented));
+            }
+        }
+        Self { patterns }
+    }
+}
+
+impl<T> PatternSet<T> {
+    pub fn iter(&self) -> impl Iterator<Item = &(Orientation, Pattern<T>)> {
+        self.patterns.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+impl<T> From<Pattern<T>> for PatternSet<T> {
+    fn from(pattern: Pattern<T>) -> Self {
+        Self {
+            patterns: vec![(Orientation::IDENTITY, pattern)],
+        }
+    }
+}
+
+/// Location of a pattern found in a grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PatternMatch {
+    pos: Pos2,
+    orientation: Orientation,
+}
+
+impl PatternMatch {
+    /// Position of the top-left cell of the matched pattern.
+    pub fn pos(&self) -> Pos2 {
+        self.pos
+    }
+
+    /// Orientation of the pattern that matched.
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+}
+
+impl<T> Grid2D<T>
+where
+    T: PartialEq,
+{
+    /// Returns `true` if the pattern matches with its top-left cell placed at `pos`.
+    pub fn matches_at(&self, pattern: &Pattern<T>, pos: Pos2) -> bool {
+        let Dimensions2D { rows, cols } = pattern.dim();
+        let grid_dim = self.dim();
+        if pos.row < 0
+            || pos.col < 0
+            || pos.row as usize + rows > grid_dim.rows()
+            || pos.col as usize + cols > grid_dim.cols()
+        {
+            return false;
+        }
+
+        pattern.cells.iter().enumerate().all(|(r, row)| {
+            row.iter().enumerate().all(|(c, cell)| match cell {
+                Some(expected) => self[pos + Pos2::new(r as isize, c as isize)] == *expected,
+                None => true,
+            })
+        })
+    }
+
+    /// Finds every position and orientation where one of the patterns matches.
+    pub fn find_pattern(&self, patterns: &PatternSet<T>) -> Vec<PatternMatch> {
+        self.positions()
+            .flat_map(|pos| {
+                patterns
+                    .iter()
+                    .filter(move |(_, pattern)| self.matches_at(pattern, pos))
+                    .map(move |(orientation, _)| PatternMatch {
+                        pos,
+                        orientation: *orientation,
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX"#;
+
+    #[test]
+    fn parses_pattern_with_wildcards() {
+        let pattern = Pattern::from("M.S\n.A.");
+
+        assert_eq!(pattern.dim(), Dimensions2D::new(2, 3));
+        assert_eq!(pattern.get(Pos2::new(0, 0)), Some(&'M'));
+        assert_eq!(pattern.get(Pos2::new(0, 1)), None);
+
+        let pattern = Pattern::with_wildcard("#?\n?#", '?');
+        assert_eq!(pattern.get(Pos2::new(0, 1)), None);
+        assert_eq!(pattern.get(Pos2::new(1, 1)), Some(&'#'));
+    }
+
+    #[test]
+    fn rotates_and_flips() {
+        let pattern = Pattern::from("AB\nCD\nEF");
+
+        assert_eq!(
+            pattern.oriented(Orientation::new(1, false)),
+            Pattern::from("ECA\nFDB")
+        );
+        assert_eq!(
+            pattern.oriented(Orientation::new(2, false)),
+            Pattern::from("FE\nDC\nBA")
+        );
+        assert_eq!(
+            pattern.oriented(Orientation::new(0, true)),
+            Pattern::from("BA\nDC\nFE")
+        );
+        assert_eq!(pattern.oriented(Orientation::new(4, false)), pattern);
+    }
+
+    #[test]
+    fn removes_duplicate_orientations() {
+        assert_eq!(Pattern::from("AB\nCD").symmetries().len(), 8);
+        assert_eq!(Pattern::from("M.S\n.A.\nM.S").symmetries().len(), 4);
+        assert_eq!(Pattern::from("A.A\n.A.\nA.A").symmetries().len(), 1);
+        assert_eq!(Pattern::from("XMAS").rotations().len(), 4);
+    }
+
+    #[test]
+    fn finds_x_mas() {
+        let grid = Grid2D::from(SAMPLE);
+        let xmas = Pattern::from("M.S\n.A.\nM.S").symmetries();
+
+        let found = grid.find_pattern(&xmas);
+
+        assert_eq!(found.len(), 9);
+        assert!(found
+            .iter()
+            .any(|m| m.pos() == Pos2::new(0, 1) && m.orientation() == Orientation::IDENTITY));
+    }
+
+    #[test]
+    fn finds_xmas_in_every_direction() {
+        let grid = Grid2D::from(SAMPLE);
+        let xmas = Pattern::from("XMAS").rotations();
+
+        // diagonals are not covered by rotating a straight line
+        assert_eq!(grid.find_pattern(&xmas).len(), 8);
+    }
+
+    #[test]
+    fn pattern_must_fit_on_grid() {
+        let grid = Grid2D::from("AB\nCD");
+        let pattern = Pattern::from("BX");
+
+        assert!(!grid.matches_at(&pattern, Pos2::new(0, 1)));
+        assert!(grid.find_pattern(&PatternSet::from(pattern)).is_empty());
+    }
+}
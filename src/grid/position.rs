@@ -7,6 +7,18 @@ pub struct Pos2 {
     pub col: isize,
 }
 
+impl Pos2 {
+    pub const fn new(row: isize, col: isize) -> Self {
+        Self { row, col }
+    }
+
+    /// Returns the manhattan distance between this position and another.
+    #[inline(always)]
+    pub fn manhattan(&self, other: Pos2) -> u64 {
+        self.row.abs_diff(other.row) as u64 + self.col.abs_diff(other.col) as u64
+    }
+}
+
 impl Add<Pos2> for Pos2 {
     type Output = Pos2;
 
@@ -34,3 +46,68 @@ impl From<(isize, isize)> for Pos2 {
         Self { row, col }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pos3 {
+    pub x: isize,
+    pub y: isize,
+    pub z: isize,
+}
+
+impl Pos3 {
+    pub const fn new(x: isize, y: isize, z: isize) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Offsets to the 6 positions sharing a face.
+    pub const FACES: [Pos3; 6] = [
+        Pos3::new(-1, 0, 0),
+        Pos3::new(1, 0, 0),
+        Pos3::new(0, -1, 0),
+        Pos3::new(0, 1, 0),
+        Pos3::new(0, 0, -1),
+        Pos3::new(0, 0, 1),
+    ];
+
+    /// Offsets to the 26 positions sharing a face, edge or corner.
+    pub const SURROUNDING: [Pos3; 26] = {
+        let mut offsets = [Pos3::new(0, 0, 0); 26];
+        let mut i = 0;
+        let mut n = 0;
+        while n < 27 {
+            let offset = Pos3::new(n / 9 - 1, n / 3 % 3 - 1, n % 3 - 1);
+            if n != 13 {
+                offsets[i] = offset;
+                i += 1;
+            }
+            n += 1;
+        }
+        offsets
+    };
+
+    /// Returns the manhattan distance between this position and another.
+    #[inline(always)]
+    pub fn manhattan(&self, other: Pos3) -> u64 {
+        self.x.abs_diff(other.x) as u64
+            + self.y.abs_diff(other.y) as u64
+            + self.z.abs_diff(other.z) as u64
+    }
+}
+
+impl Add<Pos3> for Pos3 {
+    type Output = Pos3;
+
+    fn add(self, rhs: Pos3) -> Self::Output {
+        Self {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+
+impl From<(isize, isize, isize)> for Pos3 {
+    fn from((x, y, z): (isize, isize, isize)) -> Self {
+        Self { x, y, z }
+    }
+}
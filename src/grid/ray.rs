@@ -0,0 +1,273 @@
+use super::{Direction, Grid2D, Pos2};
+
+/// Iterator over the cells visited by walking in a straight line from a starting position.
+///
+/// Created by `Grid2D::ray`.
+#[derive(Debug, Clone)]
+pub struct Ray<'a, T> {
+    grid: &'a Grid2D<T>,
+    start: Option<Pos2>,
+    next: Option<Pos2>,
+    direction: Direction,
+}
+
+impl<T> Iterator for Ray<'_, T> {
+    type Item = Pos2;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        self.next = if self.direction == Direction::new(0, 0) {
+            None
+        } else {
+            // on a toroidal grid the ray stops once it gets back to where it started
+            self.grid
+                .resolve(current + self.direction)
+                .filter(|pos| Some(*pos) != self.start)
+        };
+        Some(current)
+    }
+}
+
+/// Iterator over the positions on a line between two points, using Bresenham's algorithm.
+///
+/// Both end points are included.
+#[derive(Debug, Clone)]
+pub struct Line {
+    current: Pos2,
+    end: Pos2,
+    step: Pos2,
+    delta: Pos2,
+    error: isize,
+    done: bool,
+}
+
+impl Line {
+    pub fn new(start: Pos2, end: Pos2) -> Self {
+        let delta = Pos2::new((end.row - start.row).abs(), -(end.col - start.col).abs());
+        Self {
+            current: start,
+            end,
+            step: Pos2::new(
+                (end.row - start.row).signum(),
+                (end.col - start.col).signum(),
+            ),
+            delta,
+            error: delta.row + delta.col,
+            done: false,
+        }
+    }
+}
+
+impl Iterator for Line {
+    type Item = Pos2;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let current = self.current;
+        if current == self.end {
+            self.done = true;
+            return Some(current);
+        }
+
+        let doubled = 2 * self.error;
+        if doubled >= self.delta.col {
+            self.error += self.delta.col;
+            self.current.row += self.step.row;
+        }
+        if doubled <= self.delta.row {
+            self.error += self.delta.row;
+            self.current.col += self.step.col;
+        }
+
+        Some(current)
+    }
+}
+
+impl<T> Grid2D<T> {
+    /// Walks from `pos` in `direction` until leaving the grid.
+    ///
+    /// The starting position is the first item, as long as it is on the grid. On a toroidal grid
+    /// the ray wraps around the edges and ends just before returning to the starting position.
+    pub fn ray(&self, pos: Pos2, direction: Direction) -> Ray<'_, T> {
+        let start = self.resolve(pos);
+        Ray {
+            grid: self,
+            start,
+            next: start,
+            direction,
+        }
+    }
+
+    /// Walks from `pos` in `direction` until leaving the grid, or until reaching an element that
+    /// satisfies `predicate`. The matching position is not included.
+    pub fn ray_until<F>(
+        &self,
+        pos: Pos2,
+        direction: Direction,
+        mut predicate: F,
+    ) -> impl Iterator<Item = Pos2> + use<'_, T, F>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.ray(pos, direction)
+            .take_while(move |pos| !predicate(&self[*pos]))
+    }
+
+    /// Walks at most `len` cells from `pos` in `direction`, stopping early at the edge of the grid.
+    pub fn segment(
+        &self,
+        pos: Pos2,
+        direction: Direction,
+        len: usize,
+    ) -> impl Iterator<Item = Pos2> + use<'_, T> {
+        self.ray(pos, direction).take(len)
+    }
+
+    /// Returns the positions on the line from `a` to `b` that are on the grid.
+    ///
+    /// On a toroidal grid, positions past the edges are wrapped onto the grid.
+    pub fn line(&self, a: Pos2, b: Pos2) -> impl Iterator<Item = Pos2> + use<'_, T> {
+        Line::new(a, b).filter_map(|pos| self.resolve(pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Topology;
+
+    const GRID: &str = r#"MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM"#;
+
+    fn chars(grid: &Grid2D, positions: impl Iterator<Item = Pos2>) -> String {
+        positions.map(|pos| grid[pos]).collect()
+    }
+
+    #[test]
+    fn ray_walks_until_leaving_grid() {
+        let grid = Grid2D::from(GRID);
+
+        let ray = grid.ray(Pos2::new(0, 5), Direction::RIGHT);
+        assert_eq!(chars(&grid, ray), "XMASM");
+
+        let ray = grid.ray(Pos2::new(4, 0), Direction::new(-1, 1));
+        assert_eq!(chars(&grid, ray), "XSXMX");
+    }
+
+    #[test]
+    fn ray_is_empty_when_starting_off_grid() {
+        let grid = Grid2D::from(GRID);
+
+        assert_eq!(grid.ray(Pos2::new(5, 0), Direction::UP).count(), 0);
+    }
+
+    #[test]
+    fn ray_does_not_loop_forever_without_a_direction() {
+        let grid = Grid2D::from(GRID);
+
+        assert_eq!(grid.ray(Pos2::new(1, 1), Direction::new(0, 0)).count(), 1);
+    }
+
+    #[test]
+    fn ray_stops_before_matching_element() {
+        let grid = Grid2D::from(GRID);
+
+        let ray = grid.ray_until(Pos2::new(0, 0), Direction::RIGHT, |ch| *ch == 'X');
+        assert_eq!(chars(&grid, ray), "MMMS");
+
+        // no match means we walk off the grid
+        let ray = grid.ray_until(Pos2::new(0, 0), Direction::DOWN, |ch| *ch == 'S');
+        assert_eq!(chars(&grid, ray), "MMAMX");
+    }
+
+    #[test]
+    fn segment_has_fixed_length() {
+        let grid = Grid2D::from(GRID);
+
+        let segment = grid.segment(Pos2::new(0, 4), Direction::RIGHT, 4);
+        assert_eq!(chars(&grid, segment), "XXMA");
+    }
+
+    #[test]
+    fn segment_stops_at_edge_of_grid() {
+        let grid = Grid2D::from(GRID);
+
+        let segment = grid.segment(Pos2::new(0, 8), Direction::RIGHT, 4);
+        assert_eq!(chars(&grid, segment), "SM");
+
+        let segment = grid.segment(Pos2::new(2, 0), Direction::UP, 4);
+        assert_eq!(chars(&grid, segment), "AMM");
+    }
+
+    #[test]
+    fn draws_straight_and_diagonal_lines() {
+        let line = Line::new(Pos2::new(0, 0), Pos2::new(0, 3)).collect::<Vec<_>>();
+        assert_eq!(
+            line,
+            vec![
+                Pos2::new(0, 0),
+                Pos2::new(0, 1),
+                Pos2::new(0, 2),
+                Pos2::new(0, 3)
+            ]
+        );
+
+        let line = Line::new(Pos2::new(3, 3), Pos2::new(0, 0)).collect::<Vec<_>>();
+        assert_eq!(
+            line,
+            vec![
+                Pos2::new(3, 3),
+                Pos2::new(2, 2),
+                Pos2::new(1, 1),
+                Pos2::new(0, 0)
+            ]
+        );
+
+        let line = Line::new(Pos2::new(2, 2), Pos2::new(2, 2)).collect::<Vec<_>>();
+        assert_eq!(line, vec![Pos2::new(2, 2)]);
+    }
+
+    #[test]
+    fn draws_shallow_lines() {
+        let line = Line::new(Pos2::new(0, 0), Pos2::new(2, 6)).collect::<Vec<_>>();
+
+        assert_eq!(line.len(), 7);
+        assert_eq!(line[0], Pos2::new(0, 0));
+        assert_eq!(line[6], Pos2::new(2, 6));
+        // each step moves exactly one column
+        for pair in line.windows(2) {
+            assert_eq!(pair[1].col - pair[0].col, 1);
+            assert!((pair[1].row - pair[0].row).abs() <= 1);
+        }
+    }
+
+    #[test]
+    fn ray_wraps_around_toroidal_grid() {
+        let grid = Grid2D::from(GRID).with_topology(Topology::Toroidal);
+
+        let ray = grid.ray(Pos2::new(0, 7), Direction::RIGHT);
+        assert_eq!(chars(&grid, ray), "ASMMMMSXXM");
+
+        // steps of 2 on an even width only visit every other column before looping
+        let ray = grid.ray(Pos2::new(0, 0), Direction::new(0, 2));
+        assert_eq!(chars(&grid, ray), "MMXMS");
+
+        let ray = grid.ray(Pos2::new(-1, 0), Direction::DOWN);
+        assert_eq!(chars(&grid, ray), "XMMAM");
+    }
+
+    #[test]
+    fn line_is_clipped_to_grid() {
+        let grid = Grid2D::from(GRID);
+
+        let line = grid.line(Pos2::new(-2, 0), Pos2::new(2, 0));
+        assert_eq!(chars(&grid, line), "MMA");
+    }
+}
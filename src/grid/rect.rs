@@ -0,0 +1,102 @@
+use super::Pos2;
+
+/// A rectangle on the grid. Both corners are inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rect {
+    top_left: Pos2,
+    bottom_right: Pos2,
+}
+
+impl Rect {
+    pub fn new(top_left: Pos2, bottom_right: Pos2) -> Self {
+        Self {
+            top_left,
+            bottom_right,
+        }
+    }
+
+    /// Returns the smallest rectangle containing all of the positions, or `None` if there are no
+    /// positions.
+    pub fn bounding<I>(positions: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = Pos2>,
+    {
+        let mut positions = positions.into_iter();
+        let first = positions.next()?;
+        Some(positions.fold(Rect::new(first, first), |rect, pos| rect.expand_to(pos)))
+    }
+
+    #[inline(always)]
+    pub fn top_left(&self) -> Pos2 {
+        self.top_left
+    }
+
+    #[inline(always)]
+    pub fn bottom_right(&self) -> Pos2 {
+        self.bottom_right
+    }
+
+    #[inline(always)]
+    pub fn rows(&self) -> usize {
+        (self.bottom_right.row - self.top_left.row + 1).max(0) as usize
+    }
+
+    #[inline(always)]
+    pub fn cols(&self) -> usize {
+        (self.bottom_right.col - self.top_left.col + 1).max(0) as usize
+    }
+
+    /// Number of cells covered by the rectangle.
+    #[inline(always)]
+    pub fn area(&self) -> usize {
+        self.rows() * self.cols()
+    }
+
+    #[rustfmt::skip]
+    #[inline(always)]
+    pub fn contains<P>(&self, pos: P) -> bool
+    where
+        P: Into<Pos2>,
+    {
+        let pos = pos.into();
+        pos.row >= self.top_left.row && pos.row <= self.bottom_right.row  // row
+            && pos.col >= self.top_left.col && pos.col <= self.bottom_right.col // col
+    }
+
+    /// Returns the smallest rectangle containing both this rectangle and `pos`.
+    pub fn expand_to(self, pos: Pos2) -> Self {
+        Rect {
+            top_left: Pos2::new(
+                self.top_left.row.min(pos.row),
+                self.top_left.col.min(pos.col),
+            ),
+            bottom_right: Pos2::new(
+                self.bottom_right.row.max(pos.row),
+                self.bottom_right.col.max(pos.col),
+            ),
+        }
+    }
+
+    /// Returns an iterator over every position in the rectangle, in row order.
+    pub fn positions(&self) -> impl Iterator<Item = Pos2> {
+        let Rect {
+            top_left,
+            bottom_right,
+        } = *self;
+        (top_left.row..=bottom_right.row).flat_map(move |row| {
+            (top_left.col..=bottom_right.col).map(move |col| Pos2::new(row, col))
+        })
+    }
+}
+
+impl<P> From<(P, P)> for Rect
+where
+    P: Into<Pos2>,
+{
+    fn from((top_left, bottom_right): (P, P)) -> Self {
+        Self {
+            top_left: top_left.into(),
+            bottom_right: bottom_right.into(),
+        }
+    }
+}
@@ -0,0 +1,409 @@
+//! Geometry of a set of grid cells: area, perimeter, sides, corners and holes.
+//!
+//! Cells are connected up, down, left and right. Corners are reported on the lattice points
+//! between cells, where vertex `(r, c)` is the top-left corner of cell `(r, c)`.
+
+use fxhash::{FxHashMap, FxHashSet};
+
+use super::{Dimensions2D, Direction, Grid2D, Pos2, Rect};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CornerKind {
+    /// The region turns outwards, like the corners of a square.
+    Convex,
+    /// The region turns inwards, like the inside of an `L`.
+    Concave,
+}
+
+/// A point on the boundary where the fence changes direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Corner {
+    vertex: Pos2,
+    kind: CornerKind,
+}
+
+impl Corner {
+    /// Lattice point of the corner. Vertex `(r, c)` is the top-left corner of cell `(r, c)`.
+    pub fn vertex(&self) -> Pos2 {
+        self.vertex
+    }
+
+    pub fn kind(&self) -> CornerKind {
+        self.kind
+    }
+}
+
+/// A straight run of fence along one side of the region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fence {
+    facing: Direction,
+    from: Pos2,
+    to: Pos2,
+}
+
+impl Fence {
+    /// Which side of the fenced cells the fence is on.
+    pub fn facing(&self) -> Direction {
+        self.facing
+    }
+
+    /// First cell (top or left) bordered by the fence.
+    pub fn from(&self) -> Pos2 {
+        self.from
+    }
+
+    /// Last cell (bottom or right) bordered by the fence.
+    pub fn to(&self) -> Pos2 {
+        self.to
+    }
+
+    /// Number of cell edges making up the fence.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        (self.from.manhattan(self.to) + 1) as usize
+    }
+}
+
+/// A set of cells on a grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    cells: FxHashSet<Pos2>,
+}
+
+impl Region {
+    pub fn new<I>(cells: I) -> Self
+    where
+        I: IntoIterator<Item = Pos2>,
+    {
+        Self {
+            cells: cells.into_iter().collect(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, pos: Pos2) -> bool {
+        self.cells.contains(&pos)
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = Pos2> + '_ {
+        self.cells.iter().copied()
+    }
+
+    /// Smallest rectangle containing the region, or `None` if the region is empty.
+    pub fn bounds(&self) -> Option<Rect> {
+        Rect::bounding(self.cells())
+    }
+
+    /// Number of cells in the region.
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Number of cell edges that border a cell outside of the region.
+    pub fn perimeter(&self) -> usize {
+        self.cells()
+            .map(|pos| {
+                Direction::CARDINAL
+                    .iter()
+                    .filter(|direction| !self.contains(pos + **direction))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Number of straight sides. Equal to the number of fences and to the number of corners.
+    pub fn sides(&self) -> usize {
+        self.corners().len()
+    }
+
+    /// Returns all corners of the region, including those around holes.
+    ///
+    /// A vertex where two cells of the region only touch diagonally is reported twice, once for
+    /// each cell, since the fence turns there twice.
+    pub fn corners(&self) -> Vec<Corner> {
+        // every vertex of a cell in the region is a candidate
+        let vertices = self
+            .cells()
+            .flat_map(|Pos2 { row, col }| {
+                [(0, 0), (0, 1), (1, 0), (1, 1)].map(|(r, c)| Pos2::new(row + r, col + c))
+            })
+            .collect::<FxHashSet<_>>();
+
+        let mut corners = Vec::default();
+        for vertex in vertices {
+            let Pos2 { row, col } = vertex;
+            let nw = self.contains(Pos2::new(row - 1, col - 1));
+            let ne = self.contains(Pos2::new(row - 1, col));
+            let sw = self.contains(Pos2::new(row, col - 1));
+            let se = self.contains(Pos2::new(row, col));
+
+            #[rustfmt::skip]
+            let found = match (nw, ne,
+                               sw, se) {
+                // a single cell touches the vertex
+                (true, false,
+                 false, false)
+                | (false, true,
+                   false, false)
+                | (false, false,
+                   true, false)
+                | (false, false,
+                   false, true) => [Some(CornerKind::Convex), None],
+
+                // three cells wrap around the vertex
+                (false, true,
+                 true, true)
+                | (true, false,
+                   true, true)
+                | (true, true,
+                   false, true)
+                | (true, true,
+                   true, false) => [Some(CornerKind::Concave), None],
+
+                // two cells touching diagonally
+                (true, false,
+                 false, true)
+                | (false, true,
+                   true, false) => [Some(CornerKind::Convex), Some(CornerKind::Convex)],
+
+                _ => [None, None],
+            };
+
+            corners.extend(
+                found
+                    .into_iter()
+                    .flatten()
+                    .map(|kind| Corner { vertex, kind }),
+            );
+        }
+
+        corners
+    }
+
+    /// Returns the straight fences around the region, including those around holes.
+    pub fn fences(&self) -> Vec<Fence> {
+        // unit fences grouped by the line they sit on
+        let mut lines: FxHashMap<(Direction, isize), Vec<isize>> = FxHashMap::default();
+        for pos in self.cells() {
+            for facing in Direction::CARDINAL {
+                if self.contains(pos + facing) {
+                    continue;
+                }
+                let (line, along) = if facing.row() != 0 {
+                    (pos.row, pos.col)
+                } else {
+                    (pos.col, pos.row)
+                };
+                lines.entry((facing, line)).or_default().push(along);
+            }
+        }
+
+        let mut fences = Vec::default();
+        for ((facing, line), mut along) in lines {
+            along.sort_unstable();
+
+            let to_pos = |n: isize| {
+                if facing.row() != 0 {
+                    Pos2::new(line, n)
+                } else {
+                    Pos2::new(n, line)
+                }
+            };
+
+            let mut start = along[0];
+            for pair in along.windows(2) {
+                if pair[1] != pair[0] + 1 {
+                    fences.push(Fence {
+                        facing,
+                        from: to_pos(start),
+                        to: to_pos(pair[0]),
+                    });
+                    start = pair[1];
+                }
+            }
+            fences.push(Fence {
+                facing,
+                from: to_pos(start),
+                to: to_pos(along[along.len() - 1]),
+            });
+        }
+
+        fences
+    }
+
+    /// Returns the groups of cells that are not part of the region but are completely enclosed by
+    /// it.
+    pub fn holes(&self) -> Vec<Region> {
+        let Some(bounds) = self.bounds() else {
+            return Vec::default();
+        };
+
+        // pad by one cell so everything outside of the region is connected along the border
+        let origin = bounds.top_left() + Direction::new(-1, -1);
+        let dim = Dimensions2D::new(bounds.rows() + 2, bounds.cols() + 2);
+        let local = Grid2D::from_fn(dim, |pos| self.contains(origin + pos));
+
+        let components = local.components(|a, b| a == b);
+        let outside = components.labels()[Pos2::new(0, 0)];
+
+        components
+            .regions()
+            .iter()
+            .filter(|component| component.label() != outside && !local[component.cells()[0]])
+            .map(|component| Region::new(component.cells().iter().map(|pos| origin + *pos)))
+            .collect()
+    }
+}
+
+impl FromIterator<Pos2> for Region {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Pos2>,
+    {
+        Region::new(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Makes a region from all `X` characters.
+    fn region(shape: &str) -> Region {
+        let grid = Grid2D::from(shape);
+        grid.positions().filter(|pos| grid[*pos] == 'X').collect()
+    }
+
+    #[test]
+    fn single_cell() {
+        let region = region("X");
+
+        assert_eq!(region.area(), 1);
+        assert_eq!(region.perimeter(), 4);
+        assert_eq!(region.sides(), 4);
+        assert_eq!(region.fences().len(), 4);
+        assert!(region
+            .corners()
+            .iter()
+            .all(|corner| corner.kind() == CornerKind::Convex));
+        assert!(region.holes().is_empty());
+    }
+
+    #[test]
+    fn concave_l_shape() {
+        let region = region("X.\nXX");
+
+        assert_eq!(region.area(), 3);
+        assert_eq!(region.perimeter(), 8);
+        assert_eq!(region.sides(), 6);
+        assert_eq!(region.fences().len(), 6);
+
+        let concave = region
+            .corners()
+            .into_iter()
+            .filter(|corner| corner.kind() == CornerKind::Concave)
+            .collect::<Vec<_>>();
+        assert_eq!(concave.len(), 1);
+        assert_eq!(concave[0].vertex(), Pos2::new(1, 1));
+    }
+
+    #[test]
+    fn concave_u_shape() {
+        let region = region("X.X\nXXX");
+
+        assert_eq!(region.area(), 5);
+        assert_eq!(region.perimeter(), 12);
+        assert_eq!(region.sides(), 8);
+        assert_eq!(region.fences().len(), 8);
+        assert!(region.holes().is_empty());
+    }
+
+    #[test]
+    fn merges_fence_segments() {
+        let region = region("XXX\nX..");
+
+        let mut top = region
+            .fences()
+            .into_iter()
+            .filter(|fence| fence.facing() == Direction::UP)
+            .collect::<Vec<_>>();
+        top.sort_by_key(|fence| fence.from());
+
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].from(), Pos2::new(0, 0));
+        assert_eq!(top[0].to(), Pos2::new(0, 2));
+        assert_eq!(top[0].len(), 3);
+
+        let total = region.fences().iter().map(Fence::len).sum::<usize>();
+        assert_eq!(total, region.perimeter());
+    }
+
+    #[test]
+    fn enclosed_region() {
+        let region = region("XXX\nX.X\nXXX");
+
+        assert_eq!(region.area(), 8);
+        assert_eq!(region.perimeter(), 16);
+        assert_eq!(region.sides(), 8);
+
+        let holes = region.holes();
+        assert_eq!(holes.len(), 1);
+        assert_eq!(holes[0].area(), 1);
+        assert!(holes[0].contains(Pos2::new(1, 1)));
+    }
+
+    #[test]
+    fn enclosed_regions_touching_diagonally() {
+        let region = region(
+            r#"XXXXXX
+XXX..X
+XXX..X
+X..XXX
+X..XXX
+XXXXXX"#,
+        );
+
+        assert_eq!(region.area(), 28);
+        assert_eq!(region.sides(), 12);
+        assert_eq!(region.fences().len(), 12);
+
+        let mut holes = region.holes().iter().map(Region::area).collect::<Vec<_>>();
+        holes.sort();
+        assert_eq!(holes, vec![4, 4]);
+    }
+
+    #[test]
+    fn diagonal_cells_have_two_corners_at_the_same_vertex() {
+        let region = region("X.\n.X");
+
+        assert_eq!(region.sides(), 8);
+        let shared = region
+            .corners()
+            .into_iter()
+            .filter(|corner| corner.vertex() == Pos2::new(1, 1))
+            .count();
+        assert_eq!(shared, 2);
+    }
+
+    #[test]
+    fn e_shape() {
+        let region = region(
+            r#"XXXXX
+X....
+XXXXX
+X....
+XXXXX"#,
+        );
+
+        assert_eq!(region.area(), 17);
+        assert_eq!(region.sides(), 12);
+        assert!(region.holes().is_empty());
+    }
+
+    #[test]
+    fn open_region_has_no_holes() {
+        let region = region("XXX\nX..\nXXX");
+
+        assert!(region.holes().is_empty());
+    }
+}
@@ -0,0 +1,405 @@
+//! Rendering grids for debugging, as plain text, ANSI coloured text or ratatui `Text`.
+//!
+//! Each cell is drawn using a closure that picks a character and style for the element. Overlays
+//! are then drawn on top, so a path or a set of positions can be highlighted without changing the
+//! underlying grid.
+
+use std::fmt;
+
+use fxhash::FxHashSet;
+use owo_colors::{AnsiColors, DynColors};
+use ratatui::text::{Line, Span, Text};
+
+use super::{Grid2D, Pos2};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Colour {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Rgb(u8, u8, u8),
+}
+
+impl Colour {
+    /// Red, green and blue components, using the xterm values for the named colours.
+    pub fn rgb(&self) -> [u8; 3] {
+        match *self {
+            Colour::Black => [0, 0, 0],
+            Colour::Red => [205, 0, 0],
+            Colour::Green => [0, 205, 0],
+            Colour::Yellow => [205, 205, 0],
+            Colour::Blue => [0, 0, 238],
+            Colour::Magenta => [205, 0, 205],
+            Colour::Cyan => [0, 205, 205],
+            Colour::White => [229, 229, 229],
+            Colour::Rgb(r, g, b) => [r, g, b],
+        }
+    }
+}
+
+impl From<Colour> for DynColors {
+    fn from(colour: Colour) -> Self {
+        match colour {
+            Colour::Black => DynColors::Ansi(AnsiColors::Black),
+            Colour::Red => DynColors::Ansi(AnsiColors::Red),
+            Colour::Green => DynColors::Ansi(AnsiColors::Green),
+            Colour::Yellow => DynColors::Ansi(AnsiColors::Yellow),
+            Colour::Blue => DynColors::Ansi(AnsiColors::Blue),
+            Colour::Magenta => DynColors::Ansi(AnsiColors::Magenta),
+            Colour::Cyan => DynColors::Ansi(AnsiColors::Cyan),
+            Colour::White => DynColors::Ansi(AnsiColors::White),
+            Colour::Rgb(r, g, b) => DynColors::Rgb(r, g, b),
+        }
+    }
+}
+
+impl From<Colour> for ratatui::style::Color {
+    fn from(colour: Colour) -> Self {
+        use ratatui::style::Color;
+        match colour {
+            Colour::Black => Color::Black,
+            Colour::Red => Color::Red,
+            Colour::Green => Color::Green,
+            Colour::Yellow => Color::Yellow,
+            Colour::Blue => Color::Blue,
+            Colour::Magenta => Color::Magenta,
+            Colour::Cyan => Color::Cyan,
+            Colour::White => Color::White,
+            Colour::Rgb(r, g, b) => Color::Rgb(r, g, b),
+        }
+    }
+}
+
+/// Foreground, background and weight of a rendered cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Style {
+    fg: Option<Colour>,
+    bg: Option<Colour>,
+    bold: bool,
+}
+
+impl Style {
+    /// No colours, normal weight.
+    pub const PLAIN: Style = Style {
+        fg: None,
+        bg: None,
+        bold: false,
+    };
+
+    pub fn fg(self, colour: Colour) -> Self {
+        Self {
+            fg: Some(colour),
+            ..self
+        }
+    }
+
+    pub fn bg(self, colour: Colour) -> Self {
+        Self {
+            bg: Some(colour),
+            ..self
+        }
+    }
+
+    pub fn bold(self) -> Self {
+        Self { bold: true, ..self }
+    }
+
+    /// Applies `other` on top of this style. Colours set on `other` replace the existing ones.
+    pub fn patch(self, other: Style) -> Self {
+        Self {
+            fg: other.fg.or(self.fg),
+            bg: other.bg.or(self.bg),
+            bold: self.bold || other.bold,
+        }
+    }
+
+    fn to_owo(self) -> owo_colors::Style {
+        let mut style = owo_colors::Style::new();
+        if let Some(fg) = self.fg {
+            style = style.color(DynColors::from(fg));
+        }
+        if let Some(bg) = self.bg {
+            style = style.on_color(DynColors::from(bg));
+        }
+        if self.bold {
+            style = style.bold();
+        }
+        style
+    }
+
+    fn to_ratatui(self) -> ratatui::style::Style {
+        let mut style = ratatui::style::Style::default();
+        if let Some(fg) = self.fg {
+            style = style.fg(fg.into());
+        }
+        if let Some(bg) = self.bg {
+            style = style.bg(bg.into());
+        }
+        if self.bold {
+            style = style.add_modifier(ratatui::style::Modifier::BOLD);
+        }
+        style
+    }
+}
+
+/// A single rendered character and its style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cell {
+    ch: char,
+    style: Style,
+}
+
+impl Cell {
+    pub fn new(ch: char, style: Style) -> Self {
+        Self { ch, style }
+    }
+
+    pub fn ch(&self) -> char {
+        self.ch
+    }
+
+    pub fn style(&self) -> Style {
+        self.style
+    }
+}
+
+impl From<char> for Cell {
+    fn from(ch: char) -> Self {
+        Self::new(ch, Style::PLAIN)
+    }
+}
+
+/// Positions drawn on top of the grid.
+#[derive(Debug, Clone)]
+struct Overlay {
+    positions: FxHashSet<Pos2>,
+    ch: Option<char>,
+    style: Style,
+}
+
+/// Renders a grid with optional overlays.
+///
+/// Created by `Grid2D::render`.
+pub struct Renderer<'a, T, F> {
+    grid: &'a Grid2D<T>,
+    cell: F,
+    overlays: Vec<Overlay>,
+}
+
+impl<T, F> Renderer<'_, T, F>
+where
+    F: Fn(Pos2, &T) -> Cell,
+{
+    /// Restyles the cells at `positions`, keeping their characters.
+    ///
+    /// Overlays are drawn in the order they are added.
+    pub fn overlay<I>(mut self, positions: I, style: Style) -> Self
+    where
+        I: IntoIterator<Item = Pos2>,
+    {
+        self.overlays.push(Overlay {
+            positions: positions.into_iter().collect(),
+            ch: None,
+            style,
+        });
+        self
+    }
+
+    /// Draws `ch` at `positions` with the given style.
+    ///
+    /// Overlays are drawn in the order they are added.
+    pub fn overlay_with<I>(mut self, positions: I, ch: char, style: Style) -> Self
+    where
+        I: IntoIterator<Item = Pos2>,
+    {
+        self.overlays.push(Overlay {
+            positions: positions.into_iter().collect(),
+            ch: Some(ch),
+            style,
+        });
+        self
+    }
+
+    /// Returns the final cell at `pos` after applying the overlays.
+    pub fn cell(&self, pos: Pos2) -> Cell {
+        self.overlays
+            .iter()
+            .filter(|overlay| overlay.positions.contains(&pos))
+            .fold((self.cell)(pos, &self.grid[pos]), |cell, overlay| Cell {
+                ch: overlay.ch.unwrap_or(cell.ch),
+                style: cell.style.patch(overlay.style),
+            })
+    }
+
+    fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = Cell> + '_> + '_ {
+        let dim = self.grid.dim();
+        (0..dim.rows()).map(move |row| {
+            (0..dim.cols()).map(move |col| self.cell(Pos2::new(row as isize, col as isize)))
+        })
+    }
+
+    /// Renders the characters only, one line per row.
+    pub fn to_plain(&self) -> String {
+        self.to_string()
+    }
+
+    /// Renders with ANSI escape codes for the colours, one line per row.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::default();
+        for row in self.rows() {
+            for cell in row {
+                out.push_str(&cell.style.to_owo().style(cell.ch).to_string());
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Renders into a ratatui `Text`, one `Line` per row. Runs of cells with the same style share
+    /// a `Span`.
+    pub fn to_text(&self) -> Text<'static> {
+        let lines = self
+            .rows()
+            .map(|row| {
+                let mut spans: Vec<(Style, String)> = Vec::default();
+                for cell in row {
+                    match spans.last_mut() {
+                        Some((style, text)) if *style == cell.style => text.push(cell.ch),
+                        _ => spans.push((cell.style, cell.ch.to_string())),
+                    }
+                }
+                Line::from(
+                    spans
+                        .into_iter()
+                        .map(|(style, text)| Span::styled(text, style.to_ratatui()))
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        Text::from(lines)
+    }
+}
+
+impl<T, F> fmt::Display for Renderer<'_, T, F>
+where
+    F: Fn(Pos2, &T) -> Cell,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell.ch)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Grid2D<T> {
+    /// Starts rendering the grid, drawing each element with `cell`.
+    pub fn render<F>(&self, cell: F) -> Renderer<'_, T, F>
+    where
+        F: Fn(Pos2, &T) -> Cell,
+    {
+        Renderer {
+            grid: self,
+            cell,
+            overlays: Vec::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRID: &str = r#"#..
+.#.
+..#"#;
+
+    #[test]
+    fn renders_plain_text() {
+        let grid = Grid2D::from(GRID);
+
+        let rendered = grid.render(|_, ch| Cell::from(*ch)).to_plain();
+        assert_eq!(rendered, "#..\n.#.\n..#\n");
+
+        let grid = Grid2D::from_fn(grid.dim(), |pos| pos.row * 3 + pos.col);
+        let rendered = grid
+            .render(|_, n| Cell::from(char::from_digit(*n as u32, 10).unwrap()))
+            .to_plain();
+        assert_eq!(rendered, "012\n345\n678\n");
+    }
+
+    #[test]
+    fn overlays_are_drawn_in_order() {
+        let grid = Grid2D::from(GRID);
+        let path = [Pos2::new(0, 1), Pos2::new(0, 2), Pos2::new(1, 2)];
+
+        let renderer = grid
+            .render(|_, ch| Cell::from(*ch))
+            .overlay_with(path, 'o', Style::PLAIN.fg(Colour::Red))
+            .overlay_with([Pos2::new(1, 2)], 'X', Style::PLAIN.bold());
+
+        assert_eq!(renderer.to_plain(), "#oo\n.#X\n..#\n");
+        assert_eq!(
+            renderer.cell(Pos2::new(1, 2)).style(),
+            Style::PLAIN.fg(Colour::Red).bold()
+        );
+    }
+
+    #[test]
+    fn restyling_overlay_keeps_characters() {
+        let grid = Grid2D::from(GRID);
+
+        let renderer = grid
+            .render(|_, ch| Cell::from(*ch))
+            .overlay([Pos2::new(0, 0)], Style::PLAIN.bg(Colour::Blue));
+
+        assert_eq!(renderer.to_plain(), "#..\n.#.\n..#\n");
+        assert_eq!(
+            renderer.cell(Pos2::new(0, 0)),
+            Cell::new('#', Style::PLAIN.bg(Colour::Blue))
+        );
+    }
+
+    #[test]
+    fn renders_ansi_colours() {
+        let grid = Grid2D::from("#.");
+
+        let ansi = grid
+            .render(|_, ch| match ch {
+                '#' => Cell::new('#', Style::PLAIN.fg(Colour::Red)),
+                _ => Cell::from(*ch),
+            })
+            .to_ansi();
+
+        assert_eq!(ansi, "\u{1b}[31m#\u{1b}[0m.\n");
+    }
+
+    #[test]
+    fn renders_ratatui_text_with_merged_spans() {
+        let grid = Grid2D::from("##..\n....");
+
+        let text = grid
+            .render(|_, ch| match ch {
+                '#' => Cell::new('#', Style::PLAIN.fg(Colour::Green)),
+                _ => Cell::from(*ch),
+            })
+            .to_text();
+
+        assert_eq!(text.lines.len(), 2);
+        assert_eq!(text.lines[0].spans.len(), 2);
+        assert_eq!(text.lines[0].spans[0].content, "##");
+        assert_eq!(
+            text.lines[0].spans[0].style.fg,
+            Some(ratatui::style::Color::Green)
+        );
+        assert_eq!(text.lines[1].spans.len(), 1);
+    }
+}
@@ -0,0 +1,332 @@
+use std::{hash::Hash, ops::Index};
+
+use fxhash::{FxHashMap, FxHashSet};
+
+use super::{Dimensions2D, Direction, Finder, Grid2D, Pos2, Query, Rect};
+
+/// Unbounded grid that only stores the positions holding a value.
+///
+/// Positions may be negative, and the bounding box grows and shrinks as values are inserted and
+/// removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    inner: FxHashMap<Pos2, T>,
+    bounds: Option<Rect>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self {
+            inner: FxHashMap::default(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copies the elements of a dense grid that satisfy `keep`.
+    pub fn from_grid<F>(grid: &Grid2D<T>, mut keep: F) -> Self
+    where
+        T: Clone,
+        F: FnMut(&T) -> bool,
+    {
+        grid.positions()
+            .filter(|pos| keep(&grid[*pos]))
+            .map(|pos| (pos, grid[pos].clone()))
+            .collect()
+    }
+
+    /// Sets the element at `pos`, returning the previous element.
+    pub fn insert(&mut self, pos: Pos2, item: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => bounds.expand_to(pos),
+            None => Rect::new(pos, pos),
+        });
+        self.inner.insert(pos, item)
+    }
+
+    /// Removes the element at `pos`, returning it.
+    pub fn remove(&mut self, pos: Pos2) -> Option<T> {
+        let removed = self.inner.remove(&pos)?;
+
+        // the bounding box can only shrink when removing from its edge
+        if let Some(bounds) = self.bounds {
+            let (top_left, bottom_right) = (bounds.top_left(), bounds.bottom_right());
+            if pos.row == top_left.row
+                || pos.col == top_left.col
+                || pos.row == bottom_right.row
+                || pos.col == bottom_right.col
+            {
+                self.bounds = Rect::bounding(self.inner.keys().copied());
+            }
+        }
+
+        Some(removed)
+    }
+
+    /// Returns a reference to the element at `pos`, or `None` if empty.
+    #[inline(always)]
+    pub fn get_ref<P>(&self, pos: P) -> Option<&T>
+    where
+        P: Into<Pos2>,
+    {
+        self.inner.get(&pos.into())
+    }
+
+    /// Returns a mutable reference to the element at `pos`, or `None` if empty.
+    #[inline(always)]
+    pub fn get_mut<P>(&mut self, pos: P) -> Option<&mut T>
+    where
+        P: Into<Pos2>,
+    {
+        self.inner.get_mut(&pos.into())
+    }
+
+    /// Returns `true` if there is an element at `pos`.
+    #[inline(always)]
+    pub fn contains<P>(&self, pos: P) -> bool
+    where
+        P: Into<Pos2>,
+    {
+        self.inner.contains_key(&pos.into())
+    }
+
+    /// Smallest rectangle containing every element, or `None` if the grid is empty.
+    #[inline(always)]
+    pub fn bounds(&self) -> Option<Rect> {
+        self.bounds
+    }
+
+    /// Dimensions of the bounding box.
+    pub fn dim(&self) -> Dimensions2D {
+        self.bounds
+            .map(|bounds| Dimensions2D::new(bounds.rows(), bounds.cols()))
+            .unwrap_or(Dimensions2D::new(0, 0))
+    }
+
+    /// Number of elements stored.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns the positions adjacent (up, down, left, right) to `pos` that hold an element.
+    #[inline(always)]
+    pub fn neighbours(&self, pos: Pos2) -> impl Iterator<Item = Pos2> + use<'_, T> {
+        Direction::CARDINAL
+            .into_iter()
+            .map(move |direction| pos + direction)
+            .filter(|pos| self.inner.contains_key(pos))
+    }
+
+    /// Returns an iterator over every element and its position, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos2, &T)> {
+        self.inner.iter().map(|(pos, item)| (*pos, item))
+    }
+
+    /// Queries the grid at the given position.
+    ///
+    /// This allows you to run arbitrary code starting from a given position.
+    #[inline(always)]
+    pub fn query<Q>(&self, mut query: Q, pos: Pos2) -> Q::Output
+    where
+        Q: Query<Self>,
+    {
+        query.query(self, pos)
+    }
+
+    /// Copies the bounding box into a dense grid, using `empty` for positions without an element.
+    ///
+    /// Position `(0, 0)` on the dense grid is the top-left of `bounds`. Returns `None` if the grid
+    /// is empty.
+    pub fn to_grid(&self, empty: T) -> Option<Grid2D<T>>
+    where
+        T: Clone,
+    {
+        let bounds = self.bounds?;
+        let offset = bounds.top_left();
+        Some(Grid2D::from_fn(self.dim(), |pos| {
+            self.inner
+                .get(&(offset + pos))
+                .cloned()
+                .unwrap_or_else(|| empty.clone())
+        }))
+    }
+}
+
+impl<T> SparseGrid<T>
+where
+    T: Copy,
+{
+    /// Returns the element at `pos`, or `None` if empty.
+    #[inline(always)]
+    pub fn get<P>(&self, pos: P) -> Option<T>
+    where
+        P: Into<Pos2>,
+    {
+        self.get_ref(pos).copied()
+    }
+
+    /// Finds all elements satisfying the `Finder` implementation, in row order.
+    pub fn find_all<F>(&self, finder: F) -> Vec<(Pos2, T)>
+    where
+        F: Finder<T>,
+    {
+        let mut found = self.find_all_iter(finder).collect::<Vec<_>>();
+        found.sort_by_key(|(pos, _)| *pos);
+        found
+    }
+
+    /// Finds all elements satisfying the `Finder` implementation, returning an iterator over the
+    /// results in no particular order.
+    pub fn find_all_iter<F>(&self, finder: F) -> impl Iterator<Item = (Pos2, T)> + use<'_, F, T>
+    where
+        F: Finder<T>,
+    {
+        self.inner
+            .iter()
+            .filter(move |(_, item)| finder.check(**item))
+            .map(|(pos, item)| (*pos, *item))
+    }
+}
+
+impl<T> SparseGrid<T>
+where
+    T: Copy + Eq + Hash,
+{
+    /// Returns all unique elements present in the grid.
+    pub fn unique(&self) -> FxHashSet<T> {
+        self.inner.values().copied().collect()
+    }
+}
+
+impl<T> Index<Pos2> for SparseGrid<T> {
+    type Output = T;
+
+    #[inline(always)]
+    fn index(&self, pos: Pos2) -> &Self::Output {
+        &self.inner[&pos]
+    }
+}
+
+impl<T> FromIterator<(Pos2, T)> for SparseGrid<T> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (Pos2, T)>,
+    {
+        let mut grid = SparseGrid::default();
+        for (pos, item) in iter {
+            grid.insert(pos, item);
+        }
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct Wall;
+
+    impl Finder for Wall {
+        fn check(&self, ch: char) -> bool {
+            ch == '#'
+        }
+    }
+
+    struct CountNeighbours;
+
+    impl Query<SparseGrid<char>> for CountNeighbours {
+        type Output = usize;
+
+        fn query(&mut self, grid: &SparseGrid<char>, pos: Pos2) -> Self::Output {
+            grid.neighbours(pos).count()
+        }
+    }
+
+    fn sample() -> SparseGrid<char> {
+        [
+            (Pos2::new(-2, -3), '#'),
+            (Pos2::new(0, 0), 'S'),
+            (Pos2::new(0, 1), '#'),
+            (Pos2::new(4, 1), '#'),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn tracks_bounds_with_negative_positions() {
+        let mut grid = sample();
+
+        assert_eq!(grid.bounds(), Some(Rect::from(((-2, -3), (4, 1)))));
+        assert_eq!(grid.dim(), Dimensions2D::new(7, 5));
+
+        // removing from the inside doesn't change anything
+        grid.remove(Pos2::new(0, 0));
+        assert_eq!(grid.bounds(), Some(Rect::from(((-2, -3), (4, 1)))));
+
+        grid.remove(Pos2::new(-2, -3));
+        assert_eq!(grid.bounds(), Some(Rect::from(((0, 1), (4, 1)))));
+
+        grid.remove(Pos2::new(0, 1));
+        grid.remove(Pos2::new(4, 1));
+        assert_eq!(grid.bounds(), None);
+        assert!(grid.is_empty());
+    }
+
+    #[test]
+    fn gets_and_inserts() {
+        let mut grid = sample();
+
+        assert_eq!(grid.get((0, 0)), Some('S'));
+        assert_eq!(grid.get((100, 100)), None);
+        assert_eq!(grid.insert(Pos2::new(0, 0), 'X'), Some('S'));
+        assert_eq!(grid[Pos2::new(0, 0)], 'X');
+        assert_eq!(grid.len(), 4);
+    }
+
+    #[test]
+    fn finds_neighbours_and_elements() {
+        let grid = sample();
+
+        assert_eq!(
+            grid.neighbours(Pos2::new(0, 0)).collect::<Vec<_>>(),
+            vec![Pos2::new(0, 1)]
+        );
+        assert_eq!(grid.query(CountNeighbours, Pos2::new(1, 1)), 1);
+        assert_eq!(
+            grid.find_all(Wall),
+            vec![
+                (Pos2::new(-2, -3), '#'),
+                (Pos2::new(0, 1), '#'),
+                (Pos2::new(4, 1), '#')
+            ]
+        );
+        assert_eq!(grid.unique().len(), 2);
+    }
+
+    #[test]
+    fn converts_to_and_from_dense_grid() {
+        let dense = Grid2D::from("#..\n.S.\n..#");
+
+        let sparse = SparseGrid::from_grid(&dense, |ch| *ch != '.');
+        assert_eq!(sparse.len(), 3);
+        assert_eq!(sparse.to_grid('.'), Some(dense));
+
+        let grid = sample().to_grid('.').unwrap();
+        assert_eq!(grid.dim(), Dimensions2D::new(7, 5));
+        assert_eq!(grid.get((0, 0)), Some('#'));
+        assert_eq!(grid.get((2, 3)), Some('S'));
+
+        assert_eq!(SparseGrid::<char>::new().to_grid('.'), None);
+    }
+}
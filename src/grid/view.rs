@@ -0,0 +1,414 @@
+use std::ops::Index;
+
+use super::{Dimensions2D, Grid2D, Pos2, Rect};
+
+/// A read-only view of a grid that has been rotated, reflected or cropped. No data is copied.
+///
+/// Views can be chained, so `grid.rotate90_view().window(rect)` crops the rotated grid.
+#[derive(Debug)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid2D<T>,
+    dim: Dimensions2D,
+    // position on the grid of the view's (0, 0)
+    origin: Pos2,
+    // grid offset when moving one row down the view
+    row_step: Pos2,
+    // grid offset when moving one column right on the view
+    col_step: Pos2,
+}
+
+// derived Clone/Copy would require `T: Clone`
+impl<T> Clone for GridView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GridView<'_, T> {}
+
+impl<'a, T> GridView<'a, T> {
+    fn new(grid: &'a Grid2D<T>) -> Self {
+        Self {
+            grid,
+            dim: grid.dim(),
+            origin: Pos2::new(0, 0),
+            row_step: Pos2::new(1, 0),
+            col_step: Pos2::new(0, 1),
+        }
+    }
+
+    #[inline(always)]
+    fn grid_pos(&self, Pos2 { row, col }: Pos2) -> Pos2 {
+        Pos2::new(
+            self.origin.row + row * self.row_step.row + col * self.col_step.row,
+            self.origin.col + row * self.row_step.col + col * self.col_step.col,
+        )
+    }
+
+    /// Unchecked lookup, keeping the lifetime of the underlying grid.
+    #[inline(always)]
+    fn at(&self, pos: Pos2) -> &'a T {
+        &self.grid[self.grid_pos(pos)]
+    }
+
+    #[inline(always)]
+    pub fn dim(&self) -> Dimensions2D {
+        self.dim
+    }
+
+    /// Returns `true` if the given position is on the view.
+    #[inline(always)]
+    pub fn on_view<P>(&self, pos: P) -> bool
+    where
+        P: Into<Pos2>,
+    {
+        let pos = pos.into();
+        (pos.row as usize) < self.dim.rows() && (pos.col as usize) < self.dim.cols()
+    }
+
+    /// Returns the element at `pos` on the view, or `None` if off the view.
+    #[inline(always)]
+    pub fn get<P>(&self, pos: P) -> Option<&'a T>
+    where
+        P: Into<Pos2>,
+    {
+        let pos = pos.into();
+        if self.on_view(pos) {
+            Some(self.at(pos))
+        } else {
+            None
+        }
+    }
+
+    /// Swaps rows and columns.
+    pub fn transpose(self) -> Self {
+        Self {
+            dim: Dimensions2D::new(self.dim.cols(), self.dim.rows()),
+            row_step: self.col_step,
+            col_step: self.row_step,
+            ..self
+        }
+    }
+
+    /// Rotates a quarter turn clockwise.
+    pub fn rotate90(self) -> Self {
+        let last_row = self.dim.rows() as isize - 1;
+        Self {
+            dim: Dimensions2D::new(self.dim.cols(), self.dim.rows()),
+            origin: self.grid_pos(Pos2::new(last_row, 0)),
+            row_step: self.col_step,
+            col_step: Pos2::new(-self.row_step.row, -self.row_step.col),
+            ..self
+        }
+    }
+
+    /// Mirrors left to right.
+    pub fn flip_h(self) -> Self {
+        let last_col = self.dim.cols() as isize - 1;
+        Self {
+            origin: self.grid_pos(Pos2::new(0, last_col)),
+            col_step: Pos2::new(-self.col_step.row, -self.col_step.col),
+            ..self
+        }
+    }
+
+    /// Mirrors top to bottom.
+    pub fn flip_v(self) -> Self {
+        let last_row = self.dim.rows() as isize - 1;
+        Self {
+            origin: self.grid_pos(Pos2::new(last_row, 0)),
+            row_step: Pos2::new(-self.row_step.row, -self.row_step.col),
+            ..self
+        }
+    }
+
+    /// Crops to `rect`, given in view coordinates. Any part of `rect` outside of the view is
+    /// ignored.
+    pub fn window(self, rect: Rect) -> Self {
+        let top_left = Pos2::new(rect.top_left().row.max(0), rect.top_left().col.max(0));
+        let bottom_right = Pos2::new(
+            rect.bottom_right().row.min(self.dim.rows() as isize - 1),
+            rect.bottom_right().col.min(self.dim.cols() as isize - 1),
+        );
+        let clipped = Rect::new(top_left, bottom_right);
+        Self {
+            dim: Dimensions2D::new(clipped.rows(), clipped.cols()),
+            origin: self.grid_pos(top_left),
+            ..self
+        }
+    }
+
+    /// Returns an iterator over the elements of row `row`.
+    pub fn row(&self, row: usize) -> impl Iterator<Item = &'a T> + use<'a, T> {
+        let view = *self;
+        (0..self.dim.cols()).map(move |col| view.at(Pos2::new(row as isize, col as isize)))
+    }
+
+    /// Returns an iterator over the elements of column `col`.
+    pub fn col(&self, col: usize) -> impl Iterator<Item = &'a T> + use<'a, T> {
+        let view = *self;
+        (0..self.dim.rows()).map(move |row| view.at(Pos2::new(row as isize, col as isize)))
+    }
+
+    /// Returns an iterator over each row of the view.
+    pub fn iter_rows(
+        &self,
+    ) -> impl Iterator<Item = impl Iterator<Item = &'a T> + use<'a, T>> + use<'a, T> {
+        let view = *self;
+        (0..self.dim.rows()).map(move |row| view.row(row))
+    }
+
+    /// Returns an iterator over each column of the view.
+    pub fn iter_cols(
+        &self,
+    ) -> impl Iterator<Item = impl Iterator<Item = &'a T> + use<'a, T>> + use<'a, T> {
+        let view = *self;
+        (0..self.dim.cols()).map(move |col| view.col(col))
+    }
+
+    /// Returns an iterator over every position on the view and its element, in row order.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos2, &'a T)> + use<'a, T> {
+        let view = *self;
+        let Dimensions2D { rows, cols } = self.dim;
+        (0..rows).flat_map(move |row| {
+            (0..cols).map(move |col| {
+                let pos = Pos2::new(row as isize, col as isize);
+                (pos, view.at(pos))
+            })
+        })
+    }
+}
+
+impl<T> GridView<'_, T>
+where
+    T: Clone,
+{
+    /// Copies the view into a new grid.
+    ///
+    /// An empty view gives a grid with no rows, or with rows of no columns.
+    pub fn to_grid(&self) -> Grid2D<T> {
+        Grid2D::new(self.iter_rows().map(|row| row.cloned().collect()).collect())
+    }
+}
+
+impl<T> Index<Pos2> for GridView<'_, T> {
+    type Output = T;
+
+    #[inline(always)]
+    fn index(&self, pos: Pos2) -> &Self::Output {
+        assert!(
+            self.on_view(pos),
+            "position {pos:?} is off the {}x{} view",
+            self.dim.rows(),
+            self.dim.cols()
+        );
+        self.at(pos)
+    }
+}
+
+impl<T> Grid2D<T> {
+    /// Returns a view of the whole grid.
+    pub fn view(&self) -> GridView<'_, T> {
+        GridView::new(self)
+    }
+
+    /// Returns a view with rows and columns swapped.
+    pub fn transpose_view(&self) -> GridView<'_, T> {
+        self.view().transpose()
+    }
+
+    /// Returns a view rotated a quarter turn clockwise.
+    pub fn rotate90_view(&self) -> GridView<'_, T> {
+        self.view().rotate90()
+    }
+
+    /// Returns a view mirrored left to right.
+    pub fn flip_h_view(&self) -> GridView<'_, T> {
+        self.view().flip_h()
+    }
+
+    /// Returns a view mirrored top to bottom.
+    pub fn flip_v_view(&self) -> GridView<'_, T> {
+        self.view().flip_v()
+    }
+
+    /// Returns a view of the part of the grid covered by `rect`.
+    pub fn window(&self, rect: Rect) -> GridView<'_, T> {
+        self.view().window(rect)
+    }
+
+    /// Returns an iterator over each row of the grid.
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> {
+        self.inner.iter().map(Vec::as_slice)
+    }
+
+    /// Returns an iterator over each column of the grid.
+    pub fn iter_cols(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        let cols = self.dim().cols();
+        (0..cols).map(move |col| self.inner.iter().map(move |row| &row[col]))
+    }
+}
+
+impl<T> Grid2D<T>
+where
+    T: Clone,
+{
+    /// Returns a copy of the grid with rows and columns swapped.
+    pub fn transpose(&self) -> Self {
+        self.transpose_view().to_grid()
+    }
+
+    /// Returns a copy of the grid rotated a quarter turn clockwise.
+    pub fn rotate90(&self) -> Self {
+        self.rotate90_view().to_grid()
+    }
+
+    /// Returns a copy of the grid mirrored left to right.
+    pub fn flip_h(&self) -> Self {
+        self.flip_h_view().to_grid()
+    }
+
+    /// Returns a copy of the grid mirrored top to bottom.
+    pub fn flip_v(&self) -> Self {
+        self.flip_v_view().to_grid()
+    }
+
+    /// Returns a copy of the part of the grid covered by `rect`.
+    pub fn crop(&self, rect: Rect) -> Self {
+        self.window(rect).to_grid()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRID: &str = r#"abc
+def"#;
+
+    fn rows(view: GridView<'_, char>) -> Vec<String> {
+        view.iter_rows().map(|row| row.collect()).collect()
+    }
+
+    #[test]
+    fn views_whole_grid() {
+        let grid = Grid2D::from(GRID);
+
+        assert_eq!(rows(grid.view()), vec!["abc", "def"]);
+        assert_eq!(grid.view().get((1, 2)), Some(&'f'));
+        assert_eq!(grid.view().get((2, 0)), None);
+    }
+
+    #[test]
+    fn transposes() {
+        let grid = Grid2D::from(GRID);
+
+        let view = grid.transpose_view();
+        assert_eq!(view.dim(), Dimensions2D::new(3, 2));
+        assert_eq!(rows(view), vec!["ad", "be", "cf"]);
+    }
+
+    #[test]
+    fn rotates_clockwise() {
+        let grid = Grid2D::from(GRID);
+
+        assert_eq!(rows(grid.rotate90_view()), vec!["da", "eb", "fc"]);
+        assert_eq!(rows(grid.rotate90_view().rotate90()), vec!["fed", "cba"]);
+        assert_eq!(
+            rows(grid.rotate90_view().rotate90().rotate90().rotate90()),
+            vec!["abc", "def"]
+        );
+    }
+
+    #[test]
+    fn flips() {
+        let grid = Grid2D::from(GRID);
+
+        assert_eq!(rows(grid.flip_h_view()), vec!["cba", "fed"]);
+        assert_eq!(rows(grid.flip_v_view()), vec!["def", "abc"]);
+    }
+
+    #[test]
+    fn windows_are_clipped_to_grid() {
+        let grid = Grid2D::from(GRID);
+
+        let view = grid.window(Rect::from(((0, 1), (5, 5))));
+        assert_eq!(rows(view), vec!["bc", "ef"]);
+
+        let view = grid.window(Rect::from(((1, 1), (1, 1))));
+        assert_eq!(rows(view), vec!["e"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "off the 2x2 view")]
+    fn indexing_off_view_panics() {
+        let grid = Grid2D::from(GRID);
+
+        // (0, 2) is on the grid, but not on the view
+        let view = grid.window(Rect::from(((0, 0), (1, 1))));
+        let _ = view[Pos2::new(0, 2)];
+    }
+
+    #[test]
+    fn empty_windows() {
+        let grid = Grid2D::from(GRID);
+
+        let view = grid.window(Rect::from(((5, 5), (6, 6))));
+        assert_eq!(view.dim(), Dimensions2D::new(0, 0));
+        assert_eq!(view.get((0, 0)), None);
+        assert_eq!(view.iter().count(), 0);
+
+        let empty = view.to_grid();
+        assert_eq!(empty.dim(), Dimensions2D::new(0, 0));
+        assert_eq!(empty.positions().count(), 0);
+
+        let view = grid.window(Rect::from(((0, 5), (1, 6))));
+        assert_eq!(view.dim(), Dimensions2D::new(2, 0));
+        assert_eq!(view.to_grid().dim(), Dimensions2D::new(2, 0));
+    }
+
+    #[test]
+    fn windows_a_transformed_view() {
+        let grid = Grid2D::from(GRID);
+
+        let view = grid.rotate90_view().window(Rect::from(((1, 0), (2, 1))));
+        assert_eq!(rows(view), vec!["eb", "fc"]);
+        assert_eq!(view[Pos2::new(0, 1)], 'b');
+    }
+
+    #[test]
+    fn iterates_columns() {
+        let grid = Grid2D::from(GRID);
+
+        let cols = grid
+            .iter_cols()
+            .map(|col| col.collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(cols, vec!["ad", "be", "cf"]);
+
+        let cols = grid
+            .flip_h_view()
+            .iter_cols()
+            .map(|col| col.collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(cols, vec!["cf", "be", "ad"]);
+    }
+
+    #[test]
+    fn owned_transforms() {
+        let grid = Grid2D::from(GRID);
+
+        assert_eq!(grid.transpose(), Grid2D::from("ad\nbe\ncf"));
+        assert_eq!(grid.rotate90(), Grid2D::from("da\neb\nfc"));
+        assert_eq!(grid.flip_h(), Grid2D::from("cba\nfed"));
+        assert_eq!(grid.flip_v(), Grid2D::from("def\nabc"));
+        assert_eq!(
+            grid.crop(Rect::from(((0, 0), (1, 1)))),
+            Grid2D::from("ab\nde")
+        );
+        assert_eq!(
+            grid.iter_rows().collect::<Vec<_>>(),
+            vec![&['a', 'b', 'c'][..], &['d', 'e', 'f'][..]]
+        );
+    }
+}
@@ -2,9 +2,11 @@
 
 pub mod grid;
 pub mod mask;
+pub mod params;
 pub mod util;
 pub mod wrap;
 
+pub use params::{ParamError, Params};
 use paste::paste;
 
 macro_rules! day_modules {
@@ -40,4 +42,18 @@ pub trait AocSolver {
     type Output: std::fmt::Display;
     fn part_1(input: &str) -> Self::Output;
     fn part_2(input: &str) -> Self::Output;
+
+    /// Same as `part_1`, for puzzles that need settings that aren't part of the input.
+    ///
+    /// Fails when a parameter has a value the solver can't use.
+    fn part_1_with_params(input: &str, _params: &Params) -> Result<Self::Output, ParamError> {
+        Ok(Self::part_1(input))
+    }
+
+    /// Same as `part_2`, for puzzles that need settings that aren't part of the input.
+    ///
+    /// Fails when a parameter has a value the solver can't use.
+    fn part_2_with_params(input: &str, _params: &Params) -> Result<Self::Output, ParamError> {
+        Ok(Self::part_2(input))
+    }
 }
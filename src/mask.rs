@@ -1,14 +1,38 @@
-use std::ops::{Index, IndexMut};
+use std::ops::{BitAnd, BitOr, BitXor, Not};
 
-use crate::grid::Pos2;
+use crate::grid::{Dimensions2D, Grid2D, Pos2, Rect};
 
-#[derive(Debug)]
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A 2D set of positions, packed one bit per cell.
+///
+/// Each row starts on a new word, so rows can be combined and scanned a word at a time. A mask
+/// may have an empty border around it: positions are given relative to the inside of the border,
+/// so with a border of 1 the top-left cell of the mask is at `(-1, -1)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Mask2D {
-    mask: Vec<Vec<u8>>,
+    bits: Vec<u64>,
+    rows: usize,
+    cols: usize,
     border: usize,
 }
 
 impl Mask2D {
+    /// Creates an empty mask without a border.
+    pub fn new(dim: Dimensions2D) -> Self {
+        Self::with_border(dim.rows(), dim.cols(), 0)
+    }
+
+    /// `rows` and `cols` include the border.
+    fn with_border(rows: usize, cols: usize, border: usize) -> Self {
+        Self {
+            bits: vec![0; rows * cols.div_ceil(WORD_BITS)],
+            rows,
+            cols,
+            border,
+        }
+    }
+
     pub fn from_positions(positions: &[Pos2], border: usize) -> Self {
         let (row_len, col_len) = {
             let row_len = positions.iter().map(|pos| pos.row).max().unwrap() + 1;
@@ -16,70 +40,404 @@ impl Mask2D {
             (row_len as usize, col_len as usize)
         };
 
-        let mut mask: Vec<Vec<u8>> = Vec::with_capacity(row_len + (border * 2));
+        let mut mask = Self::with_border(row_len + (border * 2), col_len + (border * 2), border);
 
-        for _ in 0..row_len + (border * 2) {
-            mask.push(vec![0; col_len + (border * 2)])
+        for pos in positions {
+            mask.set(*pos);
         }
 
-        let mut mask = Mask2D { mask, border };
+        mask
+    }
 
-        for pos in positions {
-            mask[pos] = 1;
+    /// Creates a mask the same size as `grid`, setting every position whose element satisfies
+    /// `predicate`.
+    pub fn from_grid<T, F>(grid: &Grid2D<T>, mut predicate: F) -> Self
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut mask = Self::new(grid.dim());
+        for pos in grid.positions() {
+            if predicate(&grid[pos]) {
+                mask.set(pos);
+            }
         }
+        mask
+    }
 
+    /// Returns the dimensions of the mask, including the border.
+    #[inline(always)]
+    pub fn dim(&self) -> Dimensions2D {
+        Dimensions2D::new(self.rows, self.cols)
+    }
+
+    #[inline(always)]
+    pub fn border(&self) -> usize {
+        self.border
+    }
+
+    #[inline(always)]
+    fn words_per_row(&self) -> usize {
+        self.cols.div_ceil(WORD_BITS)
+    }
+
+    /// Maps `pos` to its word index and bit, or `None` if off the mask.
+    #[inline(always)]
+    fn locate(&self, Pos2 { row, col }: Pos2) -> Option<(usize, u64)> {
+        let border = self.border as isize;
+        let (row, col) = ((row + border) as usize, (col + border) as usize);
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+        Some((
+            row * self.words_per_row() + col / WORD_BITS,
+            1 << (col % WORD_BITS),
+        ))
+    }
+
+    #[inline(always)]
+    fn locate_or_panic(&self, pos: Pos2) -> (usize, u64) {
+        self.locate(pos)
+            .unwrap_or_else(|| panic!("position {pos:?} is off the mask"))
+    }
+
+    /// Returns the part of the mask covered by `rect`, without a border.
+    ///
+    /// `rect` uses the same coordinates as `from_positions`, so negative positions reach into the
+    /// border. Anything outside of the mask is empty.
+    pub fn crop(&self, rect: Rect) -> Self {
+        let mut mask = Self::with_border(rect.rows(), rect.cols(), 0);
+        for pos in rect.positions() {
+            if self.is_set(pos) {
+                mask.set(Pos2::new(
+                    pos.row - rect.top_left().row,
+                    pos.col - rect.top_left().col,
+                ));
+            }
+        }
         mask
     }
 
+    /// Returns `1` if the position is set, `0` if not, or `None` if off the mask.
     pub fn get<P>(&self, pos: P) -> Option<u8>
     where
         P: Into<Pos2>,
     {
-        let pos = pos.into();
-        let (row, col) = {
-            let Pos2 { row, col } = pos;
-            (col as usize + self.border, row as usize + self.border)
+        self.locate(pos.into())
+            .map(|(word, bit)| (self.bits[word] & bit != 0) as u8)
+    }
+
+    /// Returns `true` if the position is set. Positions off the mask are never set.
+    #[inline(always)]
+    pub fn is_set<P>(&self, pos: P) -> bool
+    where
+        P: Into<Pos2>,
+    {
+        self.get(pos) == Some(1)
+    }
+
+    /// Panics if `pos` is off the mask.
+    #[inline(always)]
+    pub fn set(&mut self, pos: Pos2) {
+        let (word, bit) = self.locate_or_panic(pos);
+        self.bits[word] |= bit;
+    }
+
+    /// Panics if `pos` is off the mask.
+    #[inline(always)]
+    pub fn clear(&mut self, pos: Pos2) {
+        let (word, bit) = self.locate_or_panic(pos);
+        self.bits[word] &= !bit;
+    }
+
+    /// Panics if `pos` is off the mask.
+    #[inline(always)]
+    pub fn toggle(&mut self, pos: Pos2) {
+        let (word, bit) = self.locate_or_panic(pos);
+        self.bits[word] ^= bit;
+    }
+
+    /// Number of positions that are set.
+    pub fn count_ones(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns an iterator over the positions that are set, in row order.
+    pub fn iter_set(&self) -> impl Iterator<Item = Pos2> + '_ {
+        let words_per_row = self.words_per_row();
+        let border = self.border as isize;
+        self.bits.iter().enumerate().flat_map(move |(index, word)| {
+            let row = index / words_per_row;
+            let first_col = (index % words_per_row) * WORD_BITS;
+            SetBits(*word).map(move |bit| {
+                Pos2::new(row as isize - border, (first_col + bit) as isize - border)
+            })
+        })
+    }
+
+    /// Combines two masks word by word. Both masks must have the same size and border.
+    fn zip_with<F>(&self, other: &Mask2D, f: F) -> Mask2D
+    where
+        F: Fn(u64, u64) -> u64,
+    {
+        assert_eq!(
+            (self.rows, self.cols, self.border),
+            (other.rows, other.cols, other.border),
+            "masks must have the same shape"
+        );
+        Mask2D {
+            bits: self
+                .bits
+                .iter()
+                .zip(&other.bits)
+                .map(|(a, b)| f(*a, *b))
+                .collect(),
+            ..*self
+        }
+    }
+
+    /// Positions set in both masks.
+    pub fn and(&self, other: &Mask2D) -> Mask2D {
+        self.zip_with(other, |a, b| a & b)
+    }
+
+    /// Positions set in either mask.
+    pub fn or(&self, other: &Mask2D) -> Mask2D {
+        self.zip_with(other, |a, b| a | b)
+    }
+
+    /// Positions set in exactly one of the masks.
+    pub fn xor(&self, other: &Mask2D) -> Mask2D {
+        self.zip_with(other, |a, b| a ^ b)
+    }
+
+    /// Positions that are not set, including the border.
+    pub fn not(&self) -> Mask2D {
+        let mut mask = Mask2D {
+            bits: self.bits.iter().map(|word| !word).collect(),
+            ..*self
+        };
+        mask.clear_unused_bits();
+        mask
+    }
+
+    /// Bits past the last column must stay clear so that counting and comparing works.
+    fn clear_unused_bits(&mut self) {
+        let used = self.cols % WORD_BITS;
+        if used == 0 {
+            return;
+        }
+        let words_per_row = self.words_per_row();
+        for row in self.bits.chunks_mut(words_per_row) {
+            row[words_per_row - 1] &= (1 << used) - 1;
+        }
+    }
+
+    /// Moves every set position by `offset`. Positions moved off the mask are dropped.
+    ///
+    /// Columns can only move by up to one position.
+    fn shifted(&self, offset: Pos2) -> Mask2D {
+        debug_assert!(offset.col.abs() <= 1);
+
+        let words_per_row = self.words_per_row();
+        let mut mask = Mask2D {
+            bits: vec![0; self.bits.len()],
+            ..*self
         };
+        for row in 0..self.rows {
+            let src = row as isize - offset.row;
+            if src < 0 || src as usize >= self.rows {
+                continue;
+            }
+            let src = &self.bits[src as usize * words_per_row..][..words_per_row];
+            let dst = &mut mask.bits[row * words_per_row..][..words_per_row];
+            for i in 0..words_per_row {
+                dst[i] = match offset.col {
+                    0 => src[i],
+                    // bit `n` is column `n`, so moving right is a shift left
+                    1 => (src[i] << 1) | if i > 0 { src[i - 1] >> 63 } else { 0 },
+                    _ => (src[i] >> 1) | src.get(i + 1).map(|next| next << 63).unwrap_or(0),
+                };
+            }
+        }
+        mask.clear_unused_bits();
+        mask
+    }
 
-        if row >= self.mask.len() || col >= self.mask[0].len() {
-            None
-        } else {
-            Some(self[Pos2::from((row as isize, col as isize))])
+    /// Sets every position that has a set neighbour.
+    pub fn dilate(&self, neighbourhood: Neighbourhood) -> Mask2D {
+        neighbourhood
+            .offsets()
+            .iter()
+            .fold(self.clone(), |mask, offset| mask.or(&self.shifted(*offset)))
+    }
+
+    /// Keeps only the positions whose neighbours are all set.
+    ///
+    /// Positions off the mask count as unset, so anything touching the edge of the mask is
+    /// removed. Give the mask a border to avoid that.
+    pub fn erode(&self, neighbourhood: Neighbourhood) -> Mask2D {
+        neighbourhood
+            .offsets()
+            .iter()
+            .fold(self.clone(), |mask, offset| {
+                mask.and(&self.shifted(Pos2::new(-offset.row, -offset.col)))
+            })
+    }
+
+    /// Set positions with at least one neighbour that is not set.
+    pub fn outline(&self, neighbourhood: Neighbourhood) -> Mask2D {
+        self.and(&self.erode(neighbourhood).not())
+    }
+
+    /// Sets every position that is enclosed by set positions.
+    ///
+    /// Unset positions are connected up, down, left and right, and anything connected to the edge
+    /// of the mask is outside. A border of at least 1 makes sure the outside is connected all the
+    /// way around.
+    pub fn fill_holes(&self) -> Mask2D {
+        let background = self.not();
+
+        // start from the unset positions along the edge
+        let mut outside = Mask2D {
+            bits: vec![0; self.bits.len()],
+            ..*self
+        };
+        let (rows, cols) = (self.rows as isize, self.cols as isize);
+        let border = self.border as isize;
+        for row in 0..rows {
+            for col in 0..cols {
+                if row == 0 || col == 0 || row == rows - 1 || col == cols - 1 {
+                    let pos = Pos2::new(row - border, col - border);
+                    if background.is_set(pos) {
+                        outside.set(pos);
+                    }
+                }
+            }
+        }
+
+        loop {
+            let grown = outside.dilate(Neighbourhood::Four).and(&background);
+            if grown == outside {
+                break;
+            }
+            outside = grown;
         }
+
+        outside.not()
+    }
+
+    /// Counts set positions under `kernel` centred on each position of the mask, weighting each
+    /// by the kernel value.
+    ///
+    /// The kernel should have an odd number of rows and columns. The result covers the whole mask
+    /// including the border, so mask position `pos` is at `pos + (border, border)` in the result.
+    pub fn convolve(&self, kernel: &Grid2D<u32>) -> Grid2D<u32> {
+        let kernel_dim = kernel.dim();
+        let centre = Pos2::new(
+            kernel_dim.rows() as isize / 2,
+            kernel_dim.cols() as isize / 2,
+        );
+        let border = self.border as isize;
+
+        Grid2D::from_fn(self.dim(), |pos| {
+            kernel
+                .positions()
+                .filter(|offset| {
+                    self.is_set(Pos2::new(
+                        pos.row - border + offset.row - centre.row,
+                        pos.col - border + offset.col - centre.col,
+                    ))
+                })
+                .map(|offset| kernel[offset])
+                .sum()
+        })
     }
 }
 
-impl IndexMut<&Pos2> for Mask2D {
-    fn index_mut(&mut self, Pos2 { row, col }: &Pos2) -> &mut Self::Output {
-        let border = self.border;
-        &mut self.mask[*row as usize + border][*col as usize + border]
+/// Which positions count as neighbours for morphological operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Neighbourhood {
+    /// Up, down, left and right.
+    #[default]
+    Four,
+    /// Including the diagonals.
+    Eight,
+}
+
+impl Neighbourhood {
+    pub fn offsets(&self) -> &'static [Pos2] {
+        const FOUR: [Pos2; 4] = [
+            Pos2::new(-1, 0),
+            Pos2::new(1, 0),
+            Pos2::new(0, -1),
+            Pos2::new(0, 1),
+        ];
+        const EIGHT: [Pos2; 8] = [
+            Pos2::new(-1, -1),
+            Pos2::new(-1, 0),
+            Pos2::new(-1, 1),
+            Pos2::new(0, -1),
+            Pos2::new(0, 1),
+            Pos2::new(1, -1),
+            Pos2::new(1, 0),
+            Pos2::new(1, 1),
+        ];
+        match self {
+            Neighbourhood::Four => &FOUR,
+            Neighbourhood::Eight => &EIGHT,
+        }
     }
 }
 
-impl IndexMut<Pos2> for Mask2D {
-    fn index_mut(&mut self, Pos2 { row, col }: Pos2) -> &mut Self::Output {
-        let border = self.border;
-        &mut self.mask[row as usize + border][col as usize + border]
+/// Iterator over the indices of the set bits in a word, lowest first.
+struct SetBits(u64);
+
+impl Iterator for SetBits {
+    type Item = usize;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0 == 0 {
+            return None;
+        }
+        let bit = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1;
+        Some(bit)
     }
 }
 
-impl Index<&Pos2> for Mask2D {
-    type Output = u8;
+impl BitAnd for &Mask2D {
+    type Output = Mask2D;
 
-    fn index(&self, Pos2 { row, col }: &Pos2) -> &Self::Output {
-        let border = self.border;
-        &self.mask[*row as usize + border][*col as usize + border]
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.and(rhs)
     }
 }
 
-impl Index<Pos2> for Mask2D {
-    type Output = u8;
+impl BitOr for &Mask2D {
+    type Output = Mask2D;
 
-    fn index(&self, Pos2 { row, col }: Pos2) -> &Self::Output {
-        let border = self.border;
-        let row = row + border as isize;
-        let col = col + border as isize;
-        &self.mask[row as usize][col as usize]
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.or(rhs)
+    }
+}
+
+impl BitXor for &Mask2D {
+    type Output = Mask2D;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        self.xor(rhs)
+    }
+}
+
+impl Not for &Mask2D {
+    type Output = Mask2D;
+
+    fn not(self) -> Self::Output {
+        Mask2D::not(self)
     }
 }
 
@@ -87,6 +445,19 @@ impl Index<Pos2> for Mask2D {
 mod tests {
     use super::*;
 
+    /// Every cell of the mask, including the border.
+    fn rows(mask: &Mask2D) -> Vec<Vec<u8>> {
+        let border = mask.border() as isize;
+        let dim = mask.dim();
+        (0..dim.rows() as isize)
+            .map(|row| {
+                (0..dim.cols() as isize)
+                    .map(|col| mask.get((row - border, col - border)).unwrap())
+                    .collect()
+            })
+            .collect()
+    }
+
     #[test]
     fn creates_mask_from_positions() {
         let positions = pos(&[(1, 2), (3, 4)]);
@@ -94,7 +465,7 @@ mod tests {
         let mask = Mask2D::from_positions(&positions, 0);
 
         assert_eq!(
-            mask.mask,
+            rows(&mask),
             vec![
                 vec![0, 0, 0, 0, 0],
                 vec![0, 0, 1, 0, 0],
@@ -111,7 +482,7 @@ mod tests {
         let mask = Mask2D::from_positions(&positions, 1);
 
         assert_eq!(
-            mask.mask,
+            rows(&mask),
             vec![
                 vec![0, 0, 0, 0, 0],
                 vec![0, 1, 0, 0, 0],
@@ -122,6 +493,22 @@ mod tests {
         )
     }
 
+    #[test]
+    fn crops_mask() {
+        let positions = pos(&[(0, 0), (1, 1), (2, 2)]);
+
+        let mask = Mask2D::from_positions(&positions, 1);
+
+        assert_eq!(
+            rows(&mask.crop(Rect::from(((-1, -1), (1, 1))))),
+            vec![vec![0, 0, 0], vec![0, 1, 0], vec![0, 0, 1]]
+        );
+        assert_eq!(
+            rows(&mask.crop(Rect::from(((2, 2), (4, 3))))),
+            vec![vec![1, 0], vec![0, 0], vec![0, 0]]
+        );
+    }
+
     fn pos(positions: &[(isize, isize)]) -> Vec<Pos2> {
         positions
             .iter()
@@ -142,6 +529,18 @@ mod tests {
         assert_eq!(mask.get((0, 0)), Some(0));
     }
 
+    #[test]
+    fn get_element_is_not_transposed() {
+        let positions = pos(&[(0, 2)]);
+
+        let mask = Mask2D::from_positions(&positions, 1);
+
+        assert_eq!(mask.get((0, 2)), Some(1));
+        assert_eq!(mask.get((2, 0)), None);
+        assert_eq!(mask.get((-1, -1)), Some(0));
+        assert_eq!(mask.get((0, 4)), None);
+    }
+
     #[test]
     fn creates_mask_with_offset_element() {
         let positions = pos(&[(1, 2), (3, 4)]);
@@ -149,7 +548,7 @@ mod tests {
         let mask = Mask2D::from_positions(&positions, 0);
 
         assert_eq!(
-            mask.mask,
+            rows(&mask),
             vec![
                 vec![0, 0, 0, 0, 0],
                 vec![0, 0, 1, 0, 0],
@@ -158,4 +557,156 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn sets_clears_and_toggles() {
+        let mut mask = Mask2D::new(Dimensions2D::new(2, 70));
+
+        mask.set(Pos2::new(1, 69));
+        mask.set(Pos2::new(0, 3));
+        mask.toggle(Pos2::new(0, 4));
+        assert_eq!(mask.count_ones(), 3);
+
+        mask.toggle(Pos2::new(0, 4));
+        mask.clear(Pos2::new(0, 3));
+        assert_eq!(mask.iter_set().collect::<Vec<_>>(), vec![Pos2::new(1, 69)]);
+    }
+
+    #[test]
+    fn iterates_set_positions_relative_to_border() {
+        let positions = pos(&[(0, 0), (2, 1)]);
+
+        let mask = Mask2D::from_positions(&positions, 2);
+
+        assert_eq!(mask.iter_set().collect::<Vec<_>>(), positions);
+    }
+
+    #[test]
+    fn combines_masks() {
+        let grid = Grid2D::from("##.\n.#.");
+        let a = Mask2D::from_grid(&grid, |ch| *ch == '#');
+        let b = Mask2D::from_grid(&Grid2D::from("#..\n.##"), |ch| *ch == '#');
+
+        assert_eq!(rows(&(&a & &b)), vec![vec![1, 0, 0], vec![0, 1, 0]]);
+        assert_eq!(rows(&(&a | &b)), vec![vec![1, 1, 0], vec![0, 1, 1]]);
+        assert_eq!(rows(&(&a ^ &b)), vec![vec![0, 1, 0], vec![0, 0, 1]]);
+        assert_eq!(rows(&!&a), vec![vec![0, 0, 1], vec![1, 0, 1]]);
+        // unused bits at the end of each row stay clear
+        assert_eq!(a.not().count_ones(), 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn combining_masks_of_different_shapes_panics() {
+        let a = Mask2D::new(Dimensions2D::new(2, 2));
+        let b = Mask2D::new(Dimensions2D::new(2, 3));
+
+        let _ = a.and(&b);
+    }
+
+    /// Makes a mask from all `#` characters, keeping the size of the shape.
+    fn from_shape(shape: &str, border: usize) -> Mask2D {
+        let grid = Grid2D::from(shape);
+        let dim = grid.dim();
+        let mut mask =
+            Mask2D::with_border(dim.rows() + border * 2, dim.cols() + border * 2, border);
+        for pos in grid.positions().filter(|pos| grid[*pos] == '#') {
+            mask.set(pos);
+        }
+        mask
+    }
+
+    fn shape(mask: &Mask2D) -> String {
+        rows(mask)
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|bit| if bit == 1 { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn dilates() {
+        let mask = from_shape("...\n.#.\n...", 0);
+
+        assert_eq!(shape(&mask.dilate(Neighbourhood::Four)), ".#.\n###\n.#.");
+        assert_eq!(shape(&mask.dilate(Neighbourhood::Eight)), "###\n###\n###");
+    }
+
+    #[test]
+    fn dilates_across_words() {
+        let mut mask = Mask2D::new(Dimensions2D::new(1, 130));
+        mask.set(Pos2::new(0, 63));
+        mask.set(Pos2::new(0, 128));
+
+        let dilated = mask.dilate(Neighbourhood::Four);
+        assert_eq!(
+            dilated.iter_set().collect::<Vec<_>>(),
+            pos(&[(0, 62), (0, 63), (0, 64), (0, 127), (0, 128), (0, 129)])
+        );
+    }
+
+    #[test]
+    fn erodes() {
+        let mask = from_shape("###\n###\n###", 1);
+
+        assert_eq!(
+            shape(&mask.erode(Neighbourhood::Four)),
+            ".....\n.....\n..#..\n.....\n....."
+        );
+        assert_eq!(mask.erode(Neighbourhood::Eight).count_ones(), 1);
+
+        let plus = from_shape(".#.\n###\n.#.", 1);
+        assert_eq!(plus.erode(Neighbourhood::Four).count_ones(), 1);
+        assert_eq!(plus.erode(Neighbourhood::Eight).count_ones(), 0);
+
+        // without a border everything touching the edge goes
+        let mask = from_shape("###\n###\n###", 0);
+        assert_eq!(shape(&mask.erode(Neighbourhood::Four)), "...\n.#.\n...");
+    }
+
+    #[test]
+    fn outlines() {
+        let mask = from_shape("####\n####\n####\n####", 1);
+
+        let outline = mask.outline(Neighbourhood::Four);
+        assert_eq!(outline.count_ones(), 12);
+        assert!(!outline.is_set((1, 1)));
+        assert!(outline.is_set((0, 0)));
+    }
+
+    #[test]
+    fn fills_holes() {
+        let mask = from_shape("###.\n#.#.\n###.\n#...", 1);
+
+        let filled = mask.fill_holes();
+        assert!(filled.is_set((1, 1)));
+        assert!(!filled.is_set((1, 3)));
+        assert!(!filled.is_set((3, 1)));
+        assert_eq!(filled.count_ones(), mask.count_ones() + 1);
+
+        // diagonal gaps don't connect the hole to the outside
+        let mask = from_shape(".#.\n#.#\n.#.", 1);
+        assert_eq!(mask.fill_holes().count_ones(), 5);
+    }
+
+    #[test]
+    fn convolves() {
+        let mask = from_shape("##.\n#..\n...", 0);
+        let kernel = Grid2D::new(vec![vec![1, 1, 1], vec![1, 0, 1], vec![1, 1, 1]]);
+
+        let counts = mask.convolve(&kernel);
+        assert_eq!(counts[Pos2::new(0, 0)], 2);
+        assert_eq!(counts[Pos2::new(1, 1)], 3);
+        assert_eq!(counts[Pos2::new(2, 2)], 0);
+
+        let with_border = Mask2D::from_positions(&pos(&[(0, 0)]), 1);
+        let counts = with_border.convolve(&kernel);
+        assert_eq!(counts.dim(), Dimensions2D::new(3, 3));
+        assert_eq!(counts[Pos2::new(0, 0)], 1);
+        assert_eq!(counts[Pos2::new(1, 1)], 0);
+    }
 }
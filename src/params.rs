@@ -0,0 +1,157 @@
+//! Named parameters for solvers, such as puzzle dimensions that aren't part of the input.
+//!
+//! Parameters are given to the `aoc` runner as `--param key=value` and passed to
+//! `AocSolver::part_1_with_params` and `AocSolver::part_2_with_params`.
+
+use std::{fmt::Display, str::FromStr};
+
+use fxhash::FxHashMap;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Params {
+    inner: FxHashMap<String, String>,
+}
+
+impl Params {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets `key` to `value`, replacing any previous value.
+    pub fn with<V>(mut self, key: &str, value: V) -> Self
+    where
+        V: Display,
+    {
+        self.inner.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Returns the value of `key`, or `None` if it wasn't given.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>, ParamError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.inner
+            .get(key)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|e: T::Err| ParamError::new(key, value, e))
+            })
+            .transpose()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.inner.contains_key(key)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+/// A parameter whose value a solver can't use.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamError {
+    key: String,
+    value: String,
+    reason: String,
+}
+
+impl ParamError {
+    pub fn new<R>(key: &str, value: &str, reason: R) -> Self
+    where
+        R: Display,
+    {
+        Self {
+            key: key.to_string(),
+            value: value.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl Display for ParamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid value {:?} for parameter {}: {}",
+            self.value, self.key, self.reason
+        )
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+/// Parses a single `key=value` parameter.
+pub fn parse_param(param: &str) -> Result<(String, String), String> {
+    match param.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected key=value, found {param:?}")),
+    }
+}
+
+impl FromIterator<(String, String)> for Params {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        Self {
+            inner: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_params() {
+        assert_eq!(
+            parse_param("rows=103"),
+            Ok(("rows".to_string(), "103".to_string()))
+        );
+        assert_eq!(
+            parse_param("name=a=b"),
+            Ok(("name".to_string(), "a=b".to_string()))
+        );
+        assert!(parse_param("rows").is_err());
+        assert!(parse_param("=103").is_err());
+    }
+
+    #[test]
+    fn gets_typed_values() {
+        let params = ["rows=103", "cols=101"]
+            .into_iter()
+            .map(|param| parse_param(param).unwrap())
+            .collect::<Params>();
+
+        assert_eq!(params.get::<i64>("rows"), Ok(Some(103)));
+        assert_eq!(params.get::<usize>("cols"), Ok(Some(101)));
+        assert_eq!(params.get::<i64>("depth"), Ok(None));
+        assert_eq!(
+            Params::new().with("rows", 7).get::<i64>("rows"),
+            Ok(Some(7))
+        );
+    }
+
+    #[test]
+    fn reports_unparsable_values() {
+        let error = Params::new()
+            .with("rows", "abc")
+            .get::<i64>("rows")
+            .unwrap_err();
+
+        assert_eq!(error.key(), "rows");
+        assert_eq!(
+            error.to_string(),
+            "invalid value \"abc\" for parameter rows: invalid digit found in string"
+        );
+    }
+}
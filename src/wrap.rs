@@ -1,14 +1,103 @@
-use std::ops::{Add, AddAssign};
+//! Modular arithmetic.
+//!
+//! `Wrapping` keeps a value within an inclusive range, wrapping around on overflow in either
+//! direction. The free functions cover the usual number theory needed for cycle problems.
 
+use std::{
+    fmt::Debug,
+    ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+/// Integer types that can be used with `Wrapping`.
+pub trait WrapInt:
+    Copy + Ord + Debug + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// Least non-negative remainder of `self / n`.
+    fn rem_euclid(self, n: Self) -> Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! wrap_int {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl WrapInt for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                #[inline(always)]
+                fn rem_euclid(self, n: Self) -> Self {
+                    <$t>::rem_euclid(self, n)
+                }
+
+                #[inline(always)]
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                #[inline(always)]
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, rhs)
+                }
+
+                #[inline(always)]
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, rhs)
+                }
+            }
+
+            impl PartialEq<Wrapping<$t>> for $t {
+                fn eq(&self, other: &Wrapping<$t>) -> bool {
+                    *self == other.value
+                }
+            }
+        )*
+    };
+}
+
+wrap_int![i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize];
+
+/// Integer types of up to 64 bits, whose residues can be multiplied in a `u128`.
+pub trait NarrowInt: WrapInt + Into<i128> + TryFrom<i128> + sealed::Sealed {}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! narrow_int {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl sealed::Sealed for $t {}
+            impl NarrowInt for $t {}
+        )*
+    };
+}
+
+narrow_int![i8, i16, i32, i64, u8, u16, u32, u64];
+
+/// An integer that wraps around to stay within `min..=max`.
 #[derive(Debug, Clone, Copy)]
-pub struct WrappingI64 {
-    value: i64,
-    min: i64,
-    max: i64,
+pub struct Wrapping<T> {
+    value: T,
+    min: T,
+    max: T,
 }
 
-impl WrappingI64 {
-    pub fn new(initial: i64, (min, max): (i64, i64)) -> Self {
+pub type WrappingI64 = Wrapping<i64>;
+
+impl<T> Wrapping<T>
+where
+    T: WrapInt,
+{
+    /// `initial` is expected to already be within `min..=max`.
+    pub fn new(initial: T, (min, max): (T, T)) -> Self {
         Self {
             value: initial,
             min,
@@ -16,62 +105,344 @@ impl WrappingI64 {
         }
     }
 
-    fn wrap(self, rhs: i64) -> i64 {
-        let n = self.max - self.min + 1;
-        ((self.value + rhs - self.min) % n + n) % n + self.min
+    #[inline(always)]
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    /// Number of values in the range.
+    ///
+    /// Overflows when the range covers every value of `T`. See `checked_modulus`.
+    #[inline(always)]
+    pub fn modulus(&self) -> T {
+        self.max - self.min + T::ONE
+    }
+
+    /// Number of values in the range, or `None` if it doesn't fit in `T`.
+    pub fn checked_modulus(&self) -> Option<T> {
+        self.max.checked_sub(self.min)?.checked_add(T::ONE)
+    }
+
+    /// Same as `+`, but returns `None` instead of overflowing.
+    pub fn checked_add(self, rhs: T) -> Option<Self> {
+        self.checked_modulus()?;
+        Some(self + rhs)
+    }
+
+    /// Same as `-`, but returns `None` instead of overflowing.
+    pub fn checked_sub(self, rhs: T) -> Option<Self> {
+        self.checked_modulus()?;
+        Some(self - rhs)
+    }
+
+    /// Same as `*`, but returns `None` instead of overflowing.
+    pub fn checked_mul(self, rhs: T) -> Option<Self> {
+        let n = self.checked_modulus()?;
+        let product = self.value.rem_euclid(n).checked_mul(rhs.rem_euclid(n))?;
+        Some(self.with_value(self.place_residue(product.rem_euclid(n))))
+    }
+
+    /// Maps a remainder modulo `modulus` onto the range.
+    #[inline(always)]
+    fn place_residue(&self, residue: T) -> T {
+        let n = self.modulus();
+        sub_mod(residue, self.min.rem_euclid(n), n) + self.min
+    }
+
+    fn wrap(self, rhs: T) -> T {
+        let n = self.modulus();
+        self.place_residue(add_mod(self.value.rem_euclid(n), rhs.rem_euclid(n), n))
+    }
+
+    fn wrap_sub(self, rhs: T) -> T {
+        let n = self.modulus();
+        self.place_residue(sub_mod(self.value.rem_euclid(n), rhs.rem_euclid(n), n))
+    }
+
+    /// The product of the residues has to fit in `T`.
+    fn wrap_mul(self, rhs: T) -> T {
+        let n = self.modulus();
+        self.place_residue((self.value.rem_euclid(n) * rhs.rem_euclid(n)).rem_euclid(n))
+    }
+
+    fn with_value(self, value: T) -> Self {
+        Self { value, ..self }
     }
+}
 
+impl Wrapping<i64> {
     pub fn as_i64(&self) -> i64 {
         self.value
     }
 }
 
-impl PartialEq<i64> for WrappingI64 {
-    fn eq(&self, other: &i64) -> bool {
-        self.value == *other
+/// Arithmetic widened to `i128`, which can't overflow for types of up to 64 bits.
+impl<T> Wrapping<T>
+where
+    T: NarrowInt,
+{
+    #[inline(always)]
+    fn wide_modulus(&self) -> i128 {
+        self.max.into() - self.min.into() + 1
+    }
+
+    /// Maps any `i128` onto the range.
+    fn place_wide(&self, n: i128) -> T {
+        let min = self.min.into();
+        let value = (n - min).rem_euclid(self.wide_modulus()) + min;
+        T::try_from(value)
+            .ok()
+            .expect("value is within the range of T")
+    }
+
+    /// Adds a step of any size.
+    pub fn add_i128(self, rhs: i128) -> Self {
+        let n = self.wide_modulus();
+        let value = self.value.into() + rhs.rem_euclid(n);
+        self.with_value(self.place_wide(value))
+    }
+
+    /// Multiplies by a factor of any size.
+    pub fn mul_i128(self, rhs: i128) -> Self {
+        let n = self.wide_modulus();
+        // residues are below 2^64, so the product fits in a u128
+        let product =
+            self.value.into().rem_euclid(n) as u128 * rhs.rem_euclid(n) as u128 % n as u128;
+        self.with_value(self.place_wide(product as i128))
+    }
+
+    /// Adds `step` repeated `times` times, as if `step * times` had been computed without
+    /// overflowing.
+    pub fn add_scaled(self, step: T, times: T) -> Self {
+        let n = self.wide_modulus();
+        let offset =
+            step.into().rem_euclid(n) as u128 * times.into().rem_euclid(n) as u128 % n as u128;
+        self.add_i128(offset as i128)
     }
 }
 
-impl PartialEq<WrappingI64> for i64 {
-    fn eq(&self, other: &WrappingI64) -> bool {
-        *self == other.value
+impl<T> PartialEq<T> for Wrapping<T>
+where
+    T: WrapInt,
+{
+    fn eq(&self, other: &T) -> bool {
+        self.value == *other
     }
 }
 
-impl PartialEq<WrappingI64> for WrappingI64 {
-    fn eq(&self, other: &WrappingI64) -> bool {
+impl<T> PartialEq<Wrapping<T>> for Wrapping<T>
+where
+    T: WrapInt,
+{
+    fn eq(&self, other: &Wrapping<T>) -> bool {
         self.value == other.value
     }
 }
 
-impl Eq for WrappingI64 {}
+impl<T> Eq for Wrapping<T> where T: WrapInt {}
 
-impl Add<i64> for WrappingI64 {
-    type Output = WrappingI64;
+impl<T> Add<T> for Wrapping<T>
+where
+    T: WrapInt,
+{
+    type Output = Wrapping<T>;
 
-    fn add(self, rhs: i64) -> Self::Output {
-        WrappingI64 {
-            value: self.wrap(rhs),
-            min: self.min,
-            max: self.max,
-        }
+    fn add(self, rhs: T) -> Self::Output {
+        self.with_value(self.wrap(rhs))
     }
 }
 
-impl Add<WrappingI64> for WrappingI64 {
-    type Output = WrappingI64;
+impl<T> Add<Wrapping<T>> for Wrapping<T>
+where
+    T: WrapInt,
+{
+    type Output = Wrapping<T>;
 
-    fn add(self, rhs: WrappingI64) -> Self::Output {
+    fn add(self, rhs: Wrapping<T>) -> Self::Output {
         rhs + self.value
     }
 }
 
-impl AddAssign<i64> for WrappingI64 {
-    fn add_assign(&mut self, rhs: i64) {
+impl<T> AddAssign<T> for Wrapping<T>
+where
+    T: WrapInt,
+{
+    fn add_assign(&mut self, rhs: T) {
         self.value = self.wrap(rhs);
     }
 }
 
+impl<T> Sub<T> for Wrapping<T>
+where
+    T: WrapInt,
+{
+    type Output = Wrapping<T>;
+
+    fn sub(self, rhs: T) -> Self::Output {
+        self.with_value(self.wrap_sub(rhs))
+    }
+}
+
+impl<T> Sub<Wrapping<T>> for Wrapping<T>
+where
+    T: WrapInt,
+{
+    type Output = Wrapping<T>;
+
+    fn sub(self, rhs: Wrapping<T>) -> Self::Output {
+        self - rhs.value
+    }
+}
+
+impl<T> SubAssign<T> for Wrapping<T>
+where
+    T: WrapInt,
+{
+    fn sub_assign(&mut self, rhs: T) {
+        self.value = self.wrap_sub(rhs);
+    }
+}
+
+impl<T> Mul<T> for Wrapping<T>
+where
+    T: WrapInt,
+{
+    type Output = Wrapping<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        self.with_value(self.wrap_mul(rhs))
+    }
+}
+
+impl<T> MulAssign<T> for Wrapping<T>
+where
+    T: WrapInt,
+{
+    fn mul_assign(&mut self, rhs: T) {
+        self.value = self.wrap_mul(rhs);
+    }
+}
+
+impl<T> Neg for Wrapping<T>
+where
+    T: WrapInt,
+{
+    type Output = Wrapping<T>;
+
+    fn neg(self) -> Self::Output {
+        let n = self.modulus();
+        let residue = sub_mod(T::ZERO, self.value.rem_euclid(n), n);
+        self.with_value(self.place_residue(residue))
+    }
+}
+
+// Residue arithmetic that never leaves `0..n`, so it can't overflow or go negative.
+
+#[inline(always)]
+fn add_mod<T: WrapInt>(a: T, b: T, n: T) -> T {
+    if a >= n - b {
+        a - (n - b)
+    } else {
+        a + b
+    }
+}
+
+#[inline(always)]
+fn sub_mod<T: WrapInt>(a: T, b: T, n: T) -> T {
+    if a >= b {
+        a - b
+    } else {
+        a + (n - b)
+    }
+}
+
+/// Returns `(g, x, y)` where `g` is the greatest common divisor of `a` and `b`, and
+/// `a * x + b * y == g`.
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (g, x, y) = extended_gcd_i128(a.into(), b.into());
+    (g as i64, x as i64, y as i64)
+}
+
+/// Same as `extended_gcd`, for values that may not fit in an `i64`.
+fn extended_gcd_i128(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Returns `x` in `0..m` where `a * x ≡ 1 (mod m)`, or `None` if `a` and `m` are not coprime
+/// or `m` is not positive.
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    if m <= 0 {
+        return None;
+    }
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// Returns `base.pow(exp) mod m`, in `0..m`, or `None` if `m` is not positive.
+pub fn mod_pow(base: i64, mut exp: u64, m: i64) -> Option<i64> {
+    if m <= 0 {
+        return None;
+    }
+    let m = i128::from(m);
+    let mut base = (base as i128).rem_euclid(m);
+    let mut result = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % m;
+        }
+        base = base * base % m;
+        exp >>= 1;
+    }
+    i64::try_from(result).ok()
+}
+
+/// Solves a system of congruences `x ≡ remainder (mod modulus)`, given as
+/// `(remainder, modulus)` pairs.
+///
+/// Returns `(x, lcm)` where `x` is the smallest non-negative solution and every solution is
+/// `x + k * lcm`. Moduli do not need to be coprime.
+///
+/// Returns `None` if the congruences contradict each other, if a modulus is not positive, or if
+/// the lcm of the moduli doesn't fit in an `i64`.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut x: i128 = 0;
+    let mut lcm: i128 = 1;
+    for &(remainder, modulus) in congruences {
+        if modulus <= 0 {
+            return None;
+        }
+        let (remainder, modulus) = (i128::from(remainder), i128::from(modulus));
+        let (g, p, _) = extended_gcd_i128(lcm, modulus);
+        let diff = remainder - x;
+        if diff % g != 0 {
+            return None;
+        }
+        // step x by multiples of the current lcm until it also satisfies this congruence
+        let step = modulus / g;
+        let k = (diff / g % step * p).rem_euclid(step);
+        x += lcm * k;
+        lcm *= step;
+        if lcm > i128::from(i64::MAX) {
+            return None;
+        }
+        x = x.rem_euclid(lcm);
+    }
+    Some((i64::try_from(x).ok()?, i64::try_from(lcm).ok()?))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -95,4 +466,381 @@ mod tests {
             assert_eq!(n, expected, "failed on index {i}");
         }
     }
+
+    #[test]
+    fn subtracts_multiplies_and_negates() {
+        let n = WrappingI64::new(2, (0, 9));
+
+        assert_eq!(n - 5, 7);
+        assert_eq!(n - 25, 7);
+        assert_eq!(n * 7, 4);
+        assert_eq!(n * -1, 8);
+        assert_eq!(-n, 8);
+        assert_eq!(-WrappingI64::new(0, (0, 9)), 0);
+
+        let n = WrappingI64::new(-2, (-4, -1));
+        assert_eq!(n - 3, -1);
+        assert_eq!(-n, -2);
+        assert_eq!(n * 3, -2);
+    }
+
+    #[test]
+    fn wraps_unsigned_and_narrow_types() {
+        let mut n = Wrapping::<u8>::new(3, (2, 6));
+        n -= 4;
+        assert_eq!(n, 4);
+        assert_eq!(-n, 6);
+        n += 255;
+        assert_eq!(n, 4);
+
+        let n = Wrapping::<i8>::new(-100, (-100, 26));
+        assert_eq!(n - 1, 26);
+        assert_eq!(n + n, -73);
+
+        let n = Wrapping::<usize>::new(0, (0, 102));
+        assert_eq!(n - 1, 102);
+    }
+
+    #[test]
+    fn finds_gcd_and_inverse() {
+        assert_eq!(extended_gcd(240, 46).0, 2);
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(240 * x + 46 * y, g);
+
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(101, 103), Some(51));
+        assert_eq!(mod_inverse(6, 9), None);
+    }
+
+    #[test]
+    fn raises_to_a_power() {
+        assert_eq!(mod_pow(4, 13, 497), Some(445));
+        assert_eq!(mod_pow(-2, 3, 5), Some(2));
+        assert_eq!(mod_pow(7, 0, 1), Some(0));
+        assert_eq!(mod_pow(i64::MAX - 1, 2, i64::MAX), Some(1));
+    }
+
+    #[test]
+    fn rejects_non_positive_moduli() {
+        assert_eq!(mod_inverse(3, 0), None);
+        assert_eq!(mod_inverse(3, -11), None);
+        assert_eq!(mod_pow(2, 5, 0), None);
+        assert_eq!(mod_pow(2, 5, -7), None);
+    }
+
+    #[test]
+    fn crt_rejects_bad_moduli() {
+        assert_eq!(crt(&[(1, 0)]), None);
+        assert_eq!(crt(&[(2, 3), (1, 0)]), None);
+        assert_eq!(crt(&[(1, -5)]), None);
+    }
+
+    #[test]
+    fn crt_rejects_lcm_overflow() {
+        assert_eq!(crt(&[(1, i64::MAX)]), Some((1, i64::MAX)));
+        assert_eq!(crt(&[(0, i64::MAX), (1, 2)]), None);
+        assert_eq!(crt(&[(1, 1 << 32), (2, (1 << 32) - 1)]), None);
+
+        // large moduli whose lcm still fits
+        let (p, q) = (3_037_000_493, 3_037_000_453);
+        let (x, lcm) = crt(&[(5, p), (7, q)]).unwrap();
+        assert_eq!(lcm, p * q);
+        assert_eq!((x % p, x % q), (5, 7));
+    }
+
+    #[test]
+    fn solves_chinese_remainder_theorem() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(15, 103), (80, 101)]).map(|(x, _)| x % 103), Some(15));
+        assert_eq!(crt(&[(15, 103), (80, 101)]).map(|(x, _)| x % 101), Some(80));
+
+        // moduli sharing a factor
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+
+        assert_eq!(crt(&[]), Some((0, 1)));
+    }
+
+    /// Arbitrary precision integer, only fast enough to check the wrapping arithmetic against.
+    #[derive(Debug, Clone)]
+    struct Big {
+        negative: bool,
+        // little-endian base 2^32
+        limbs: Vec<u32>,
+    }
+
+    impl Big {
+        fn new(n: i128) -> Self {
+            let mut magnitude = n.unsigned_abs();
+            let mut limbs = Vec::default();
+            while magnitude > 0 {
+                limbs.push(magnitude as u32);
+                magnitude >>= 32;
+            }
+            Self {
+                negative: n < 0,
+                limbs,
+            }
+        }
+
+        fn cmp_magnitude(a: &[u32], b: &[u32]) -> std::cmp::Ordering {
+            a.len()
+                .cmp(&b.len())
+                .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+        }
+
+        fn trim(mut limbs: Vec<u32>) -> Vec<u32> {
+            while limbs.last() == Some(&0) {
+                limbs.pop();
+            }
+            limbs
+        }
+
+        fn add(&self, other: &Big) -> Big {
+            let (a, b) = (&self.limbs, &other.limbs);
+            if self.negative == other.negative {
+                let mut limbs = Vec::default();
+                let mut carry = 0u64;
+                for i in 0..a.len().max(b.len()) {
+                    let sum =
+                        *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+                    limbs.push(sum as u32);
+                    carry = sum >> 32;
+                }
+                limbs.push(carry as u32);
+                return Big {
+                    negative: self.negative,
+                    limbs: Big::trim(limbs),
+                };
+            }
+
+            // subtract the smaller magnitude from the larger
+            let (large, small, negative) = match Big::cmp_magnitude(a, b) {
+                std::cmp::Ordering::Less => (b, a, other.negative),
+                _ => (a, b, self.negative),
+            };
+            let mut limbs = Vec::default();
+            let mut borrow = 0i64;
+            for (i, limb) in large.iter().enumerate() {
+                let mut diff = *limb as i64 - *small.get(i).unwrap_or(&0) as i64 - borrow;
+                borrow = (diff < 0) as i64;
+                if diff < 0 {
+                    diff += 1 << 32;
+                }
+                limbs.push(diff as u32);
+            }
+            Big {
+                negative,
+                limbs: Big::trim(limbs),
+            }
+        }
+
+        fn mul(&self, other: &Big) -> Big {
+            let mut limbs = vec![0u64; self.limbs.len() + other.limbs.len() + 1];
+            for (i, a) in self.limbs.iter().enumerate() {
+                let mut carry = 0u64;
+                for (j, b) in other.limbs.iter().enumerate() {
+                    let product = *a as u64 * *b as u64 + limbs[i + j] + carry;
+                    limbs[i + j] = product & 0xffff_ffff;
+                    carry = product >> 32;
+                }
+                limbs[i + other.limbs.len()] += carry;
+            }
+            Big {
+                negative: self.negative != other.negative,
+                limbs: Big::trim(limbs.into_iter().map(|limb| limb as u32).collect()),
+            }
+        }
+
+        fn rem_euclid(&self, n: u128) -> u128 {
+            let remainder = self.limbs.iter().rev().fold(0u128, |remainder, limb| {
+                ((remainder << 32) | *limb as u128) % n
+            });
+            if self.negative && remainder != 0 {
+                n - remainder
+            } else {
+                remainder
+            }
+        }
+    }
+
+    /// xorshift64*, so the property tests are repeatable without extra dependencies.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        fn i64(&mut self) -> i64 {
+            // bias towards the extremes, where overflow happens
+            match self.next() % 4 {
+                0 => i64::MAX - (self.next() % 1000) as i64,
+                1 => i64::MIN + (self.next() % 1000) as i64,
+                _ => self.next() as i64,
+            }
+        }
+
+        fn u64(&mut self) -> u64 {
+            match self.next() % 4 {
+                0 => u64::MAX - self.next() % 1000,
+                1 => self.next() % 1000,
+                _ => self.next(),
+            }
+        }
+
+        /// A `u64` range with `min <= max`, sometimes covering every value.
+        fn range_u64(&mut self) -> (u64, u64) {
+            let (a, b) = (self.u64(), self.u64());
+            match self.next() % 4 {
+                0 => (0, u64::MAX),
+                1 => (a.min(b), a.min(b).saturating_add(self.next() % 200)),
+                _ => (a.min(b), a.max(b)),
+            }
+        }
+
+        /// A range with `min <= max` that still has room for a modulus.
+        fn range(&mut self) -> (i64, i64) {
+            let (a, b) = (self.i64(), self.i64());
+            let (min, max) = (a.min(b), a.max(b));
+            match self.next() % 3 {
+                0 => (min, min.saturating_add((self.next() % 200) as i64)),
+                _ => (min, max),
+            }
+        }
+    }
+
+    /// Maps `n` onto `min..=max` using big integers.
+    fn model<T: NarrowInt>(n: &Big, (min, max): (T, T)) -> T {
+        let (min, max): (i128, i128) = (min.into(), max.into());
+        let modulus = (max - min + 1) as u128;
+        let offset = n.add(&Big::new(-min)).rem_euclid(modulus);
+        T::try_from(min + offset as i128)
+            .ok()
+            .expect("model value is within the range")
+    }
+
+    #[test]
+    fn wide_arithmetic_matches_big_integers() {
+        let mut rng = Rng(0x1234_5678_9abc_def0);
+        for _ in 0..10_000 {
+            let (min, max) = rng.range();
+            let value = model(&Big::new(rng.i64() as i128), (min, max));
+            let n = Wrapping::<i64>::new(value, (min, max));
+
+            let rhs = (rng.next() as i128) << 64 | rng.next() as i128;
+            assert_eq!(
+                n.add_i128(rhs),
+                model(&Big::new(value as i128).add(&Big::new(rhs)), (min, max)),
+                "{value} + {rhs} in {min}..={max}"
+            );
+            assert_eq!(
+                n.mul_i128(rhs),
+                model(&Big::new(value as i128).mul(&Big::new(rhs)), (min, max)),
+                "{value} * {rhs} in {min}..={max}"
+            );
+
+            let (step, times) = (rng.i64(), rng.i64());
+            let scaled = Big::new(step as i128).mul(&Big::new(times as i128));
+            assert_eq!(
+                n.add_scaled(step, times),
+                model(&Big::new(value as i128).add(&scaled), (min, max)),
+                "{value} + {step} * {times} in {min}..={max}"
+            );
+        }
+    }
+
+    #[test]
+    fn wide_arithmetic_matches_big_integers_for_u64() {
+        let mut rng = Rng(0x5555_aaaa_3333_cccc);
+        for _ in 0..10_000 {
+            let (min, max) = rng.range_u64();
+            let value = model(&Big::new(rng.u64() as i128), (min, max));
+            let n = Wrapping::<u64>::new(value, (min, max));
+
+            let rhs = (rng.next() as i128) << 64 | rng.next() as i128;
+            assert_eq!(
+                n.add_i128(rhs),
+                model(&Big::new(value as i128).add(&Big::new(rhs)), (min, max)),
+                "{value} + {rhs} in {min}..={max}"
+            );
+            assert_eq!(
+                n.mul_i128(rhs),
+                model(&Big::new(value as i128).mul(&Big::new(rhs)), (min, max)),
+                "{value} * {rhs} in {min}..={max}"
+            );
+
+            let (step, times) = (rng.u64(), rng.u64());
+            let scaled = Big::new(step as i128).mul(&Big::new(times as i128));
+            assert_eq!(
+                n.add_scaled(step, times),
+                model(&Big::new(value as i128).add(&scaled), (min, max)),
+                "{value} + {step} * {times} in {min}..={max}"
+            );
+        }
+    }
+
+    #[test]
+    fn checked_arithmetic_matches_big_integers() {
+        let mut rng = Rng(0x0fed_cba9_8765_4321);
+        for _ in 0..10_000 {
+            let (min, max) = rng.range();
+            let value = model(&Big::new(rng.i64() as i128), (min, max));
+            let n = Wrapping::<i64>::new(value, (min, max));
+            let rhs = rng.i64();
+
+            let Some(modulus) = n.checked_modulus() else {
+                assert!(n.checked_add(rhs).is_none());
+                continue;
+            };
+            assert_eq!(
+                n.checked_add(rhs).unwrap(),
+                model(&Big::new(value as i128 + rhs as i128), (min, max))
+            );
+            assert_eq!(
+                n.checked_sub(rhs).unwrap(),
+                model(&Big::new(value as i128 - rhs as i128), (min, max))
+            );
+            let product = value
+                .rem_euclid(modulus)
+                .checked_mul(rhs.rem_euclid(modulus));
+            assert_eq!(
+                n.checked_mul(rhs).map(|n| n.value()),
+                product.map(|_| model(&Big::new(value as i128 * rhs as i128), (min, max)))
+            );
+        }
+    }
+
+    #[test]
+    fn checked_arithmetic_detects_full_range() {
+        let n = WrappingI64::new(0, (i64::MIN, i64::MAX));
+
+        assert_eq!(n.checked_modulus(), None);
+        assert!(n.checked_add(1).is_none());
+        assert!(n.checked_mul(2).is_none());
+
+        let n = Wrapping::<u8>::new(200, (0, 254));
+        assert_eq!(n.checked_mul(2).map(|n| n.value()), None);
+        assert_eq!(n.checked_add(100).map(|n| n.value()), Some(45));
+    }
+
+    #[test]
+    fn scales_without_overflowing() {
+        let n = WrappingI64::new(0, (0, 102));
+
+        assert_eq!(
+            n.add_scaled(-3, i64::MAX),
+            (-3i128 * i64::MAX as i128).rem_euclid(103) as i64
+        );
+        assert_eq!(n.add_i128(i128::MIN), i128::MIN.rem_euclid(103) as i64);
+        assert_eq!(n.mul_i128(5), 0);
+        assert_eq!(
+            (n + 2).mul_i128(i128::MAX),
+            (i128::MAX % 103 * 2).rem_euclid(103) as i64
+        );
+    }
 }